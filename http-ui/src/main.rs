@@ -125,6 +125,17 @@ pub struct IndexerOpt {
     /// Number of parallel jobs for indexing, defaults to # of CPUs.
     #[structopt(long)]
     pub indexing_jobs: Option<usize>,
+
+    /// The character that separates the fields of the CSV documents, it must be ASCII.
+    #[structopt(long, default_value = ",", parse(try_from_str = parse_csv_delimiter))]
+    pub csv_delimiter: u8,
+}
+
+fn parse_csv_delimiter(s: &str) -> anyhow::Result<u8> {
+    match s.as_bytes() {
+        [delimiter] if delimiter.is_ascii() => Ok(*delimiter),
+        _otherwise => Err(anyhow::anyhow!("the CSV delimiter must be a single ASCII character")),
+    }
 }
 
 struct Highlighter<'a, A> {
@@ -357,7 +368,10 @@ async fn main() -> anyhow::Result<()> {
                         let mut builder = update_builder.index_documents(&mut wtxn, &index_cloned);
 
                         match format.as_str() {
-                            "csv" => builder.update_format(UpdateFormat::Csv),
+                            "csv" => {
+                                builder.update_format(UpdateFormat::Csv);
+                                builder.csv_delimiter(indexer_opt_cloned.csv_delimiter);
+                            }
                             "json" => builder.update_format(UpdateFormat::Json),
                             "json-stream" => builder.update_format(UpdateFormat::JsonStream),
                             otherwise => panic!("invalid update format {:?}", otherwise),
@@ -719,9 +733,10 @@ async fn main() -> anyhow::Result<()> {
             let rtxn = index.read_txn().unwrap();
 
             let mut search = index.search(&rtxn);
-            if let Some(query) = query.query {
-                search.query(query);
-            }
+            match query.query {
+                Some(query) if !query.trim().is_empty() => search.query(query),
+                _ => search.match_all(),
+            };
 
             let filters = match query.filters {
                 Some(condition) if !condition.trim().is_empty() => {
@@ -754,7 +769,7 @@ async fn main() -> anyhow::Result<()> {
                 search.limit(limit);
             }
 
-            let SearchResult { matching_words, candidates, documents_ids } =
+            let SearchResult { matching_words, candidates, documents_ids, .. } =
                 search.execute().unwrap();
 
             let number_of_candidates = candidates.len();
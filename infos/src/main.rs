@@ -7,7 +7,7 @@ use byte_unit::Byte;
 use heed::EnvOpenOptions;
 use milli::facet::FacetType;
 use milli::index::db_name::*;
-use milli::{FieldId, Index, TreeLevel};
+use milli::{update, FieldId, FilterCondition, Index, TreeLevel};
 use structopt::StructOpt;
 use Command::*;
 
@@ -189,6 +189,14 @@ enum Command {
         databases: Vec<String>,
     },
 
+    /// Outputs the number of entries and the size on disk of every database,
+    /// along with the number of documents and the field distribution.
+    Stats {
+        /// Outputs the statistics as JSON instead of a human readable text.
+        #[structopt(long)]
+        json: bool,
+    },
+
     /// Outputs a CSV with the proximities for the two specidied words and
     /// the documents ids where these relations appears.
     ///
@@ -244,6 +252,20 @@ enum Command {
         /// If defined, only retrieve the documents that corresponds to these internal ids.
         internal_documents_ids: Vec<u32>,
     },
+
+    /// Deletes the documents with the given external ids and the ones matching the filter,
+    /// outputs the number of deleted documents.
+    ///
+    /// This is the only command that writes into the index.
+    DeleteDocuments {
+        /// The external ids of the documents to delete.
+        #[structopt(long = "id")]
+        external_ids: Vec<String>,
+
+        /// The filter the documents to delete must match.
+        #[structopt(long)]
+        filter: Option<String>,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -296,6 +318,7 @@ fn main() -> anyhow::Result<()> {
         AverageNumberOfWordsByDoc => average_number_of_words_by_doc(&index, &rtxn),
         AverageNumberOfPositionsByWord => average_number_of_positions_by_word(&index, &rtxn),
         SizeOfDatabase { databases } => size_of_databases(&index, &rtxn, databases),
+        Stats { json } => stats(&index, &rtxn, json),
         DatabaseStats { database } => database_stats(&index, &rtxn, &database),
         WordPairProximitiesDocids { full_display, word1, word2 } => {
             word_pair_proximities_docids(&index, &rtxn, !full_display, word1, word2)
@@ -308,6 +331,11 @@ fn main() -> anyhow::Result<()> {
         ExportDocuments { internal_documents_ids } => {
             export_documents(&index, &rtxn, internal_documents_ids)
         }
+        DeleteDocuments { external_ids, filter } => {
+            // The write transaction can't be opened while the read one is still alive.
+            drop(rtxn);
+            delete_documents(&index, external_ids, filter)
+        }
     }
 }
 
@@ -959,9 +987,7 @@ fn average_number_of_positions_by_word(index: &Index, rtxn: &heed::RoTxn) -> any
     Ok(())
 }
 
-fn size_of_databases(index: &Index, rtxn: &heed::RoTxn, names: Vec<String>) -> anyhow::Result<()> {
-    use heed::types::ByteSlice;
-
+fn database_by_name<'i>(index: &'i Index, name: &str) -> anyhow::Result<&'i heed::PolyDatabase> {
     let Index {
         env: _env,
         main,
@@ -980,6 +1006,60 @@ fn size_of_databases(index: &Index, rtxn: &heed::RoTxn, names: Vec<String>) -> a
         documents,
     } = index;
 
+    let database = match name {
+        MAIN => main,
+        WORD_PREFIX_DOCIDS => word_prefix_docids.as_polymorph(),
+        WORD_DOCIDS => word_docids.as_polymorph(),
+        DOCID_WORD_POSITIONS => docid_word_positions.as_polymorph(),
+        WORD_PAIR_PROXIMITY_DOCIDS => word_pair_proximity_docids.as_polymorph(),
+        WORD_PREFIX_PAIR_PROXIMITY_DOCIDS => word_prefix_pair_proximity_docids.as_polymorph(),
+        WORD_LEVEL_POSITION_DOCIDS => word_level_position_docids.as_polymorph(),
+        WORD_PREFIX_LEVEL_POSITION_DOCIDS => word_prefix_level_position_docids.as_polymorph(),
+        FIELD_ID_WORD_COUNT_DOCIDS => field_id_word_count_docids.as_polymorph(),
+        FACET_ID_F64_DOCIDS => facet_id_f64_docids.as_polymorph(),
+        FACET_ID_STRING_DOCIDS => facet_id_string_docids.as_polymorph(),
+        FIELD_ID_DOCID_FACET_F64S => field_id_docid_facet_f64s.as_polymorph(),
+        FIELD_ID_DOCID_FACET_STRINGS => field_id_docid_facet_strings.as_polymorph(),
+
+        DOCUMENTS => documents.as_polymorph(),
+        unknown => anyhow::bail!("unknown database {:?}", unknown),
+    };
+
+    Ok(database)
+}
+
+/// Returns the total keys size, the total values size and the number of entries.
+fn database_size(
+    database: &heed::PolyDatabase,
+    rtxn: &heed::RoTxn,
+) -> heed::Result<(u64, u64, u64)> {
+    use heed::types::ByteSlice;
+
+    let mut key_size: u64 = 0;
+    let mut val_size: u64 = 0;
+    let mut number_entries: u64 = 0;
+    for result in database.iter::<_, ByteSlice, ByteSlice>(rtxn)? {
+        let (k, v) = result?;
+        key_size += k.len() as u64;
+        val_size += v.len() as u64;
+        number_entries += 1;
+    }
+
+    Ok((key_size, val_size, number_entries))
+}
+
+/// Returns the number of entries and the size on disk of a database, the size is computed
+/// from the number of pages the database uses, its entries are not read.
+fn database_disk_size(
+    database: &heed::PolyDatabase,
+    rtxn: &heed::RoTxn,
+) -> heed::Result<(u64, u64)> {
+    let stat = database.stat(rtxn)?;
+    let pages = stat.branch_pages + stat.leaf_pages + stat.overflow_pages;
+    Ok((stat.entries as u64, pages as u64 * stat.page_size as u64))
+}
+
+fn size_of_databases(index: &Index, rtxn: &heed::RoTxn, names: Vec<String>) -> anyhow::Result<()> {
     let names = if names.is_empty() {
         ALL_DATABASE_NAMES.iter().map(|s| s.to_string()).collect()
     } else {
@@ -987,34 +1067,8 @@ fn size_of_databases(index: &Index, rtxn: &heed::RoTxn, names: Vec<String>) -> a
     };
 
     for name in names {
-        let database = match name.as_str() {
-            MAIN => &main,
-            WORD_PREFIX_DOCIDS => word_prefix_docids.as_polymorph(),
-            WORD_DOCIDS => word_docids.as_polymorph(),
-            DOCID_WORD_POSITIONS => docid_word_positions.as_polymorph(),
-            WORD_PAIR_PROXIMITY_DOCIDS => word_pair_proximity_docids.as_polymorph(),
-            WORD_PREFIX_PAIR_PROXIMITY_DOCIDS => word_prefix_pair_proximity_docids.as_polymorph(),
-            WORD_LEVEL_POSITION_DOCIDS => word_level_position_docids.as_polymorph(),
-            WORD_PREFIX_LEVEL_POSITION_DOCIDS => word_prefix_level_position_docids.as_polymorph(),
-            FIELD_ID_WORD_COUNT_DOCIDS => field_id_word_count_docids.as_polymorph(),
-            FACET_ID_F64_DOCIDS => facet_id_f64_docids.as_polymorph(),
-            FACET_ID_STRING_DOCIDS => facet_id_string_docids.as_polymorph(),
-            FIELD_ID_DOCID_FACET_F64S => field_id_docid_facet_f64s.as_polymorph(),
-            FIELD_ID_DOCID_FACET_STRINGS => field_id_docid_facet_strings.as_polymorph(),
-
-            DOCUMENTS => documents.as_polymorph(),
-            unknown => anyhow::bail!("unknown database {:?}", unknown),
-        };
-
-        let mut key_size: u64 = 0;
-        let mut val_size: u64 = 0;
-        let mut number_entries: u64 = 0;
-        for result in database.iter::<_, ByteSlice, ByteSlice>(rtxn)? {
-            let (k, v) = result?;
-            key_size += k.len() as u64;
-            val_size += v.len() as u64;
-            number_entries += 1;
-        }
+        let database = database_by_name(index, &name)?;
+        let (key_size, val_size, number_entries) = database_size(database, rtxn)?;
 
         println!("The {} database weigh:", name);
         println!("\ttotal key size: {}", Byte::from(key_size).get_appropriate_unit(true));
@@ -1026,6 +1080,46 @@ fn size_of_databases(index: &Index, rtxn: &heed::RoTxn, names: Vec<String>) -> a
     Ok(())
 }
 
+fn stats(index: &Index, rtxn: &heed::RoTxn, json: bool) -> anyhow::Result<()> {
+    let number_of_documents = index.number_of_documents(rtxn)?;
+    let field_distribution = index.field_distribution(rtxn)?;
+
+    let mut databases = Vec::with_capacity(ALL_DATABASE_NAMES.len());
+    for name in ALL_DATABASE_NAMES {
+        let database = database_by_name(index, name)?;
+        let (number_entries, size) = database_disk_size(database, rtxn)?;
+        databases.push((name, number_entries, size));
+    }
+
+    if json {
+        let databases: serde_json::Map<_, _> = databases
+            .into_iter()
+            .map(|(name, entries, size)| {
+                (name.to_string(), serde_json::json!({ "entries": entries, "size": size }))
+            })
+            .collect();
+        let stats = serde_json::json!({
+            "numberOfDocuments": number_of_documents,
+            "fieldDistribution": field_distribution,
+            "databases": databases,
+        });
+        println!("{}", stats);
+    } else {
+        println!("number of documents: {}", number_of_documents);
+        println!("field distribution:");
+        for (field, count) in field_distribution {
+            println!("\t{}: {}", field, count);
+        }
+        println!("databases:");
+        for (name, entries, size) in databases {
+            let size = Byte::from(size).get_appropriate_unit(true);
+            println!("\t{}: {} entries, {}", name, entries, size);
+        }
+    }
+
+    Ok(())
+}
+
 fn database_stats(index: &Index, rtxn: &heed::RoTxn, name: &str) -> anyhow::Result<()> {
     use heed::types::ByteSlice;
     use heed::{BytesDecode, Error};
@@ -1195,3 +1289,36 @@ fn word_prefix_pair_proximities_docids(
 
     Ok(wtr.flush()?)
 }
+
+fn delete_documents(
+    index: &Index,
+    external_ids: Vec<String>,
+    filter: Option<String>,
+) -> anyhow::Result<()> {
+    if external_ids.is_empty() && filter.is_none() {
+        anyhow::bail!("You must specify the ids or a filter of the documents to delete.");
+    }
+
+    let mut wtxn = index.write_txn()?;
+    let condition = match filter {
+        Some(filter) => Some(FilterCondition::from_str(&wtxn, index, &filter)?),
+        None => None,
+    };
+
+    let mut builder = update::DeleteDocuments::new(&mut wtxn, index, 0)?;
+    for external_id in &external_ids {
+        if builder.delete_external_id(external_id).is_none() {
+            eprintln!("The document {:?} does not exist.", external_id);
+        }
+    }
+    if let Some(condition) = condition {
+        builder.delete_by_filter(condition)?;
+    }
+
+    let deleted_documents = builder.execute()?;
+    wtxn.commit()?;
+
+    println!("{} documents deleted", deleted_documents);
+
+    Ok(())
+}
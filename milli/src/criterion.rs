@@ -5,6 +5,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, UserError};
 
+/// The reserved field in which the documents can store a precomputed score, it is always
+/// faceted to be used by the [`Criterion::RankingScore`] ranking rule.
+pub const RANKING_SCORE_FIELD: &str = "_rankingScore";
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum Criterion {
     /// Sorted by decreasing number of matched query terms.
@@ -22,6 +26,11 @@ pub enum Criterion {
     Sort,
     /// Sorted by the similarity of the matched words with the query words.
     Exactness,
+    /// Sorted by increasing number of words of the shortest attribute matching the query,
+    /// documents with shorter, more focused, attributes are considered better.
+    WordCount,
+    /// Sorted by the decreasing value of the `_rankingScore` field of the documents.
+    RankingScore,
     /// Sorted by the increasing value of the field specified.
     Asc(String),
     /// Sorted by the decreasing value of the field specified.
@@ -42,22 +51,21 @@ impl FromStr for Criterion {
     type Err = Error;
 
     fn from_str(text: &str) -> Result<Criterion, Self::Err> {
-        match text {
+        match text.trim() {
             "words" => Ok(Criterion::Words),
             "typo" => Ok(Criterion::Typo),
             "proximity" => Ok(Criterion::Proximity),
             "attribute" => Ok(Criterion::Attribute),
             "sort" => Ok(Criterion::Sort),
             "exactness" => Ok(Criterion::Exactness),
+            "wordcount" => Ok(Criterion::WordCount),
+            "rankingscore" => Ok(Criterion::RankingScore),
             text => match AscDesc::from_str(text) {
                 Ok(AscDesc::Asc(field)) => Ok(Criterion::Asc(field)),
                 Ok(AscDesc::Desc(field)) => Ok(Criterion::Desc(field)),
-                Err(UserError::InvalidAscDescSyntax { name }) => {
-                    Err(UserError::InvalidCriterionName { name }.into())
-                }
-                Err(error) => {
-                    Err(UserError::InvalidCriterionName { name: error.to_string() }.into())
-                }
+                // A text that looks like an asc/desc criterion is reported as a syntax error.
+                Err(error) if text.contains(|c| c == ':' || c == '(') => Err(error.into()),
+                Err(_) => Err(UserError::InvalidCriterionName { name: text.to_string() }.into()),
             },
         }
     }
@@ -83,15 +91,33 @@ impl FromStr for AscDesc {
 
     /// Since we don't know if this was deserialized for a criterion or a sort we just return a
     /// string and let the caller create his own error
+    ///
+    /// Both the `field:asc` and the `asc(field)` syntaxes are accepted,
+    /// the whitespaces around the field name and the order are ignored.
     fn from_str(text: &str) -> Result<AscDesc, Self::Err> {
-        match text.rsplit_once(':') {
-            Some((field_name, "asc")) => Ok(AscDesc::Asc(field_name.to_string())),
-            Some((field_name, "desc")) => Ok(AscDesc::Desc(field_name.to_string())),
+        match split_asc_desc(text.trim()) {
+            Some((field_name, "asc")) if !field_name.is_empty() => {
+                Ok(AscDesc::Asc(field_name.to_string()))
+            }
+            Some((field_name, "desc")) if !field_name.is_empty() => {
+                Ok(AscDesc::Desc(field_name.to_string()))
+            }
             _ => Err(UserError::InvalidAscDescSyntax { name: text.to_string() }),
         }
     }
 }
 
+/// Splits a `field:order` or an `order(field)` text into the trimmed field name and order.
+fn split_asc_desc(text: &str) -> Option<(&str, &str)> {
+    match text.rsplit_once(':') {
+        Some((field_name, order)) => Some((field_name.trim(), order.trim())),
+        None => {
+            let (order, field_name) = text.strip_suffix(')')?.split_once('(')?;
+            Some((field_name.trim(), order.trim()))
+        }
+    }
+}
+
 pub fn default_criteria() -> Vec<Criterion> {
     vec![
         Criterion::Words,
@@ -114,8 +140,54 @@ impl fmt::Display for Criterion {
             Attribute => f.write_str("attribute"),
             Sort => f.write_str("sort"),
             Exactness => f.write_str("exactness"),
+            WordCount => f.write_str("wordcount"),
+            RankingScore => f.write_str("rankingscore"),
             Asc(attr) => write!(f, "{}:asc", attr),
             Desc(attr) => write!(f, "{}:desc", attr),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+
+    use super::*;
+
+    #[test]
+    fn parse_asc_desc_criteria() {
+        let criterion: Criterion = "asc( price )".parse().unwrap();
+        assert_eq!(criterion, Criterion::Asc(S("price")));
+        let criterion: Criterion = "desc(release_date)".parse().unwrap();
+        assert_eq!(criterion, Criterion::Desc(S("release_date")));
+        let criterion: Criterion = " price : desc ".parse().unwrap();
+        assert_eq!(criterion, Criterion::Desc(S("price")));
+        let criterion: Criterion = "release_date:asc".parse().unwrap();
+        assert_eq!(criterion, Criterion::Asc(S("release_date")));
+        let criterion: Criterion = " words ".parse().unwrap();
+        assert_eq!(criterion, Criterion::Words);
+    }
+
+    #[test]
+    fn parse_invalid_criteria() {
+        // A malformed asc/desc criterion is a syntax error, not an unknown criterion.
+        match "price:ascending".parse::<Criterion>() {
+            Err(Error::UserError(UserError::InvalidAscDescSyntax { name })) => {
+                assert_eq!(name, "price:ascending")
+            }
+            otherwise => panic!("unexpected result {:?}", otherwise),
+        }
+        match "asc()".parse::<Criterion>() {
+            Err(Error::UserError(UserError::InvalidAscDescSyntax { name })) => {
+                assert_eq!(name, "asc()")
+            }
+            otherwise => panic!("unexpected result {:?}", otherwise),
+        }
+        match "unknown".parse::<Criterion>() {
+            Err(Error::UserError(UserError::InvalidCriterionName { name })) => {
+                assert_eq!(name, "unknown")
+            }
+            otherwise => panic!("unexpected result {:?}", otherwise),
+        }
+    }
+}
@@ -23,6 +23,7 @@ pub enum Error {
 pub enum InternalError {
     DatabaseClosing,
     DatabaseMissingEntry { db_name: &'static str, key: Option<&'static str> },
+    FieldDistributionUnderflow { field: String, stored: u64, diff: u64 },
     FieldIdMapMissingEntry(FieldIdMapMissingEntry),
     Fst(fst::Error),
     GrenadInvalidCompressionType,
@@ -53,8 +54,13 @@ pub enum UserError {
     AttributeLimitReached,
     Csv(csv::Error),
     DocumentLimitReached,
+    FieldAlreadyExists { field_name: String },
+    FilterTooExpensive { field: String, scanned: usize },
+    IndexInUse,
     InvalidAscDescSyntax { name: String },
     InvalidCriterionName { name: String },
+    InvalidCsvValue { row: u64, column: String, expected: &'static str, value: String },
+    InvalidDateFacetValue { field: String, value: String },
     InvalidDocumentId { document_id: Value },
     InvalidFacetsDistribution { invalid_facets_name: HashSet<String> },
     InvalidFilter(pest::error::Error<ParserRule>),
@@ -69,6 +75,7 @@ pub enum UserError {
     NoSpaceLeftOnDevice,
     PrimaryKeyCannotBeChanged,
     PrimaryKeyCannotBeReset,
+    SearchTimedOut,
     SerdeJson(serde_json::Error),
     UnknownInternalDocumentId { document_id: DocumentId },
 }
@@ -181,6 +188,11 @@ impl fmt::Display for InternalError {
             Self::DatabaseMissingEntry { db_name, key } => {
                 write!(f, "missing {} in the {} database", key.unwrap_or("key"), db_name)
             }
+            Self::FieldDistributionUnderflow { field, stored, diff } => write!(
+                f,
+                "field distribution underflow for {}, {} occurrences stored but {} removed",
+                field, stored, diff
+            ),
             Self::FieldIdMapMissingEntry(error) => error.fmt(f),
             Self::Fst(error) => error.fmt(f),
             Self::GrenadInvalidCompressionType => {
@@ -208,6 +220,14 @@ impl fmt::Display for UserError {
             Self::AttributeLimitReached => f.write_str("maximum number of attributes reached"),
             Self::Csv(error) => error.fmt(f),
             Self::DocumentLimitReached => f.write_str("maximum number of documents reached"),
+            Self::FieldAlreadyExists { field_name } => {
+                write!(f, "the field {} already exists", field_name)
+            }
+            Self::FilterTooExpensive { field, scanned } => write!(
+                f,
+                "filter too expensive, {} facet values of the field {} were scanned",
+                scanned, field
+            ),
             Self::InvalidFacetsDistribution { invalid_facets_name } => {
                 let name_list =
                     invalid_facets_name.iter().map(AsRef::as_ref).collect::<Vec<_>>().join(", ");
@@ -217,11 +237,22 @@ impl fmt::Display for UserError {
                     name_list
                 )
             }
+            Self::IndexInUse => {
+                f.write_str("the index is still in use, all its copies must be dropped first")
+            }
             Self::InvalidFilter(error) => error.fmt(f),
             Self::InvalidAscDescSyntax { name } => {
                 write!(f, "invalid asc/desc syntax for {}", name)
             }
             Self::InvalidCriterionName { name } => write!(f, "invalid criterion {}", name),
+            Self::InvalidCsvValue { row, column, expected, value } => write!(
+                f,
+                "invalid CSV value {:?} at row {} of the column {}, expected a {}",
+                value, row, column, expected
+            ),
+            Self::InvalidDateFacetValue { field, value } => {
+                write!(f, "invalid date {:?} in the date field {}", value, field)
+            }
             Self::InvalidDocumentId { document_id } => {
                 let json = serde_json::to_string(document_id).unwrap();
                 write!(
@@ -264,6 +295,7 @@ only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and undersco
             Self::PrimaryKeyCannotBeReset => {
                 f.write_str("primary key cannot be reset if the database contains documents")
             }
+            Self::SearchTimedOut => f.write_str("the search deadline has been exceeded"),
             Self::SerdeJson(error) => error.fmt(f),
             Self::UnknownInternalDocumentId { document_id } => {
                 write!(f, "an unknown internal document id have been used ({})", document_id)
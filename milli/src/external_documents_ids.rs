@@ -6,6 +6,8 @@ use std::{fmt, str};
 use fst::map::IndexedValue;
 use fst::{IntoStreamer, Streamer};
 
+use crate::Result;
+
 const DELETED_ID: u64 = u64::MAX;
 
 pub struct ExternalDocumentsIds<'a> {
@@ -82,6 +84,15 @@ impl<'a> ExternalDocumentsIds<'a> {
         self.merge_soft_into_hard()
     }
 
+    /// Returns a lazy iterator over the external ids along with their internal ids,
+    /// in the external ids order.
+    ///
+    /// Only the fst maps are read, it is much cheaper than retrieving the external ids
+    /// from the documents themselves.
+    pub fn into_stream(self) -> ExternalDocumentsIdsStream<'a> {
+        ExternalDocumentsIdsStream { external_documents_ids: self, last_external_id: None }
+    }
+
     /// An helper function to debug this type, returns an `HashMap` of both,
     /// soft and hard fst maps, combined.
     pub fn to_hash_map(&self) -> HashMap<String, u32> {
@@ -123,6 +134,41 @@ impl<'a> ExternalDocumentsIds<'a> {
     }
 }
 
+/// An iterator over the external ids and their internal ids, in the external ids order.
+///
+/// The union of the hard and soft fst maps is streamed lazily, it is resumed right after
+/// the last returned external id every time the next entry is requested.
+pub struct ExternalDocumentsIdsStream<'a> {
+    external_documents_ids: ExternalDocumentsIds<'a>,
+    last_external_id: Option<Vec<u8>>,
+}
+
+impl Iterator for ExternalDocumentsIdsStream<'_> {
+    type Item = Result<(String, u32)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ExternalDocumentsIds { hard, soft } = &self.external_documents_ids;
+        let (hard, soft) = match &self.last_external_id {
+            Some(last) => (hard.range().gt(last), soft.range().gt(last)),
+            None => (hard.range(), soft.range()),
+        };
+
+        let mut iter = fst::map::OpBuilder::new().add(hard).add(soft).r#union();
+        while let Some((external_id, marked_docids)) = iter.next() {
+            let id = indexed_last_value(marked_docids).unwrap();
+            if id != DELETED_ID {
+                self.last_external_id = Some(external_id.to_vec());
+                return Some(match str::from_utf8(external_id) {
+                    Ok(external_id) => Ok((external_id.to_owned(), id.try_into().unwrap())),
+                    Err(error) => Err(error.into()),
+                });
+            }
+        }
+
+        None
+    }
+}
+
 impl fmt::Debug for ExternalDocumentsIds<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_tuple("ExternalDocumentsIds").field(&self.to_hash_map()).finish()
@@ -0,0 +1,52 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+/// The formats of the dates without timezone, these dates are considered to be UTC.
+const NAIVE_DATETIME_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// Parses a RFC 3339 date, a date and time without timezone or a simple `YYYY-MM-DD` date
+/// and returns the number of milliseconds since the epoch, the dates without timezone are UTC.
+///
+/// The milliseconds are exactly represented by the `f64` facet numbers for the dates
+/// between the years -285 616 and 285 616, the date facets are therefore facet numbers.
+///
+/// We reuse the `f64` facet databases instead of introducing a dedicated date codec: the
+/// facet levels, the range filters, the sort and the facet distribution already work on
+/// these numbers, a new codec would have to be dispatched per field in every one of them,
+/// and the order of the milliseconds is the chronological order of the dates.
+pub fn parse_date_millis(text: &str) -> Option<i64> {
+    let text = text.trim();
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        return Some(datetime.timestamp_millis());
+    }
+
+    for format in NAIVE_DATETIME_FORMATS {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(datetime.timestamp_millis());
+        }
+    }
+
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_hms(0, 0, 0).timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dates() {
+        assert_eq!(parse_date_millis("1970-01-01"), Some(0));
+        assert_eq!(parse_date_millis("2021-01-01"), Some(1_609_459_200_000));
+        assert_eq!(parse_date_millis("2021-01-01T00:00:01"), Some(1_609_459_201_000));
+        assert_eq!(parse_date_millis("2021-01-01 00:00:00.250"), Some(1_609_459_200_250));
+        assert_eq!(parse_date_millis("2021-01-01T00:00:00Z"), Some(1_609_459_200_000));
+        assert_eq!(parse_date_millis("2021-01-01T01:00:00+01:00"), Some(1_609_459_200_000));
+        assert_eq!(parse_date_millis("1969-12-31T23:59:59Z"), Some(-1000));
+
+        assert_eq!(parse_date_millis("2021-13-01"), None);
+        assert_eq!(parse_date_millis("yesterday"), None);
+        assert_eq!(parse_date_millis("42"), None);
+    }
+}
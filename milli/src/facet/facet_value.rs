@@ -37,6 +37,15 @@ impl From<i64> for FacetValue {
     }
 }
 
+/// The order in which the facet values of a field are iterated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FacetValueOrder {
+    /// The storage order, the strings and then the numbers, the values are lazily read.
+    ByValue,
+    /// The decreasing number of documents, all the values must be read to be sorted.
+    ByCount,
+}
+
 /// We implement Serialize ourselves because we need to always serialize it as a string,
 /// JSON object keys must be strings not numbers.
 // TODO remove this impl and convert them into string, by hand, when required.
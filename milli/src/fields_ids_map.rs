@@ -16,6 +16,23 @@ impl FieldsIdsMap {
         FieldsIdsMap { names_ids: BTreeMap::new(), ids_names: BTreeMap::new(), next_id: Some(0) }
     }
 
+    /// Creates a map where the fields ids follow the order of the given names, duplicated
+    /// names keep the id of their first occurrence. Returns `None` if the maximum field id
+    /// has been reached.
+    ///
+    /// This is useful to keep a deterministic fields ordering between different batches.
+    pub fn from_names<I, S>(names: I) -> Option<FieldsIdsMap>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut map = FieldsIdsMap::new();
+        for name in names {
+            map.insert(name.as_ref())?;
+        }
+        Some(map)
+    }
+
     /// Returns the number of fields ids in the map.
     pub fn len(&self) -> usize {
         self.names_ids.len()
@@ -59,6 +76,18 @@ impl FieldsIdsMap {
         }
     }
 
+    /// Renames a field, keeping its id. Returns `None` if the field is unknown
+    /// or if the new name is already used by another field.
+    pub fn rename(&mut self, old_name: &str, new_name: &str) -> Option<FieldId> {
+        if self.names_ids.contains_key(new_name) {
+            return None;
+        }
+        let id = self.names_ids.remove(old_name)?;
+        self.names_ids.insert(new_name.to_owned(), id);
+        self.ids_names.insert(id, new_name.to_owned());
+        Some(id)
+    }
+
     /// Iterate over the ids and names in the ids order.
     pub fn iter(&self) -> impl Iterator<Item = (FieldId, &str)> {
         self.ids_names.iter().map(|(id, name)| (*id, name.as_str()))
@@ -120,4 +149,32 @@ mod tests {
         assert_eq!(iter.next(), Some((3, "title")));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn from_names() {
+        let names = vec!["id", "title", "description", "title"];
+        let map = FieldsIdsMap::from_names(names).unwrap();
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.id("id"), Some(0));
+        assert_eq!(map.id("title"), Some(1));
+        assert_eq!(map.id("description"), Some(2));
+        assert_eq!(map.names().collect::<Vec<_>>(), vec!["id", "title", "description"]);
+    }
+
+    #[test]
+    fn rename() {
+        let mut map = FieldsIdsMap::new();
+        assert_eq!(map.insert("id"), Some(0));
+        assert_eq!(map.insert("desc"), Some(1));
+
+        assert_eq!(map.rename("desc", "id"), None);
+        assert_eq!(map.rename("unknown", "title"), None);
+        assert_eq!(map.rename("desc", "description"), Some(1));
+
+        assert_eq!(map.id("desc"), None);
+        assert_eq!(map.id("description"), Some(1));
+        assert_eq!(map.name(1), Some("description"));
+        assert_eq!(map.insert("title"), Some(2));
+    }
 }
@@ -0,0 +1,85 @@
+use std::borrow::Cow;
+
+use crate::{try_split_array_at, DocumentId};
+
+/// Encodes a document id followed by the latitude and longitude of its geo point
+/// in 20 bytes, the document id is big-endian so the points are ordered by document.
+pub struct GeoPointCodec;
+
+impl<'a> heed::BytesDecode<'a> for GeoPointCodec {
+    type DItem = (DocumentId, [f64; 2]);
+
+    fn bytes_decode(bytes: &'a [u8]) -> Option<Self::DItem> {
+        let (docid_bytes, bytes) = try_split_array_at(bytes)?;
+        let (lat_bytes, bytes) = try_split_array_at(bytes)?;
+        let (lng_bytes, bytes) = try_split_array_at(bytes)?;
+        if !bytes.is_empty() {
+            return None;
+        }
+
+        let docid = DocumentId::from_be_bytes(docid_bytes);
+        let point = [f64::from_be_bytes(lat_bytes), f64::from_be_bytes(lng_bytes)];
+        Some((docid, point))
+    }
+}
+
+impl<'a> heed::BytesEncode<'a> for GeoPointCodec {
+    type EItem = (DocumentId, [f64; 2]);
+
+    fn bytes_encode((docid, [lat, lng]): &Self::EItem) -> Option<Cow<[u8]>> {
+        let mut bytes = Vec::with_capacity(4 + 8 + 8);
+        bytes.extend_from_slice(&docid.to_be_bytes());
+        bytes.extend_from_slice(&lat.to_be_bytes());
+        bytes.extend_from_slice(&lng.to_be_bytes());
+        Some(Cow::Owned(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use heed::{BytesDecode, BytesEncode};
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let points = [
+            (0, [0.0, 0.0]),
+            (1, [48.8566, 2.3522]),
+            (42, [-33.8688, 151.2093]),
+            (u32::MAX, [90.0, 180.0]),
+            (u32::MAX - 1, [-90.0, -180.0]),
+            (7, [f64::MIN_POSITIVE, -f64::MIN_POSITIVE]),
+            (8, [f64::MAX, f64::MIN]),
+        ];
+
+        for point in &points {
+            let bytes = GeoPointCodec::bytes_encode(point).unwrap();
+            assert_eq!(bytes.len(), 20);
+            assert_eq!(GeoPointCodec::bytes_decode(&bytes).unwrap(), *point);
+        }
+    }
+
+    #[test]
+    fn stable_layout() {
+        let bytes = GeoPointCodec::bytes_encode(&(258, [1.0, -2.0])).unwrap();
+        let mut expected = vec![0, 0, 1, 2];
+        expected.extend_from_slice(&[0x3f, 0xf0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[0xc0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(bytes.as_ref(), &expected[..]);
+
+        // The points are ordered by document id.
+        let lower = GeoPointCodec::bytes_encode(&(1, [90.0, 180.0])).unwrap();
+        let upper = GeoPointCodec::bytes_encode(&(256, [-90.0, -180.0])).unwrap();
+        assert!(lower < upper);
+    }
+
+    #[test]
+    fn invalid_length() {
+        let bytes = GeoPointCodec::bytes_encode(&(1, [1.0, 2.0])).unwrap();
+        assert!(GeoPointCodec::bytes_decode(&bytes[..19]).is_none());
+        let mut longer = bytes.into_owned();
+        longer.push(0);
+        assert!(GeoPointCodec::bytes_decode(&longer).is_none());
+    }
+}
@@ -1,6 +1,7 @@
 mod beu32_str_codec;
 pub mod facet;
 mod field_id_word_count_codec;
+mod geo_point_codec;
 mod obkv_codec;
 mod roaring_bitmap;
 mod roaring_bitmap_length;
@@ -9,6 +10,7 @@ mod str_str_u8_codec;
 
 pub use self::beu32_str_codec::BEU32StrCodec;
 pub use self::field_id_word_count_codec::FieldIdWordCountCodec;
+pub use self::geo_point_codec::GeoPointCodec;
 pub use self::obkv_codec::ObkvCodec;
 pub use self::roaring_bitmap::{BoRoaringBitmapCodec, CboRoaringBitmapCodec, RoaringBitmapCodec};
 pub use self::roaring_bitmap_length::{
@@ -1,39 +1,56 @@
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::iter;
 use std::mem::size_of;
 use std::path::Path;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use either::Either;
+use fxhash::FxHasher64;
 use heed::flags::Flags;
 use heed::types::*;
-use heed::{Database, PolyDatabase, RoTxn, RwTxn};
+use heed::{CompactionOption, Database, PolyDatabase, RoTxn, RwTxn};
 use roaring::RoaringBitmap;
+use serde_json::Value;
 
-use crate::error::{InternalError, UserError};
+use crate::error::{FieldIdMapMissingEntry, InternalError, UserError};
+use crate::facet::{FacetValue, FacetValueOrder};
 use crate::fields_ids_map::FieldsIdsMap;
 use crate::heed_codec::facet::{
     FacetLevelValueF64Codec, FacetStringLevelZeroCodec, FacetStringLevelZeroValueCodec,
     FieldDocIdFacetF64Codec, FieldDocIdFacetStringCodec,
 };
+use crate::proximity::DEFAULT_MAX_PROXIMITY_DISTANCE;
 use crate::{
-    default_criteria, BEU32StrCodec, BoRoaringBitmapCodec, CboRoaringBitmapCodec, Criterion,
-    DocumentId, ExternalDocumentsIds, FacetDistribution, FieldDistribution, FieldId,
-    FieldIdWordCountCodec, ObkvCodec, Result, RoaringBitmapCodec, RoaringBitmapLenCodec, Search,
-    StrLevelPositionCodec, StrStrU8Codec, BEU32,
+    default_criteria, obkv_to_json, BEU32StrCodec, BoRoaringBitmapCodec, CboRoaringBitmapCodec,
+    Criterion, DocumentId, ExternalDocumentsIds, ExternalDocumentsIdsStream, FacetDistribution,
+    FieldDistribution, FieldId, FieldIdWordCountCodec, ObkvCodec, PrimaryKeyPolicy, Result,
+    RoaringBitmapCodec, RoaringBitmapLenCodec, Search, StrLevelPositionCodec, StrStrU8Codec, BEU32,
+    RANKING_SCORE_FIELD,
 };
 
 pub mod main_key {
     pub const CRITERIA_KEY: &str = "criteria";
+    pub const DATE_FIELDS_KEY: &str = "date-fields";
     pub const DISPLAYED_FIELDS_KEY: &str = "displayed-fields";
     pub const DISTINCT_FIELD_KEY: &str = "distinct-field-key";
     pub const DOCUMENTS_IDS_KEY: &str = "documents-ids";
+    pub const EXACTNESS_PREFIX_TIER_KEY: &str = "exactness-prefix-tier";
     pub const FILTERABLE_FIELDS_KEY: &str = "filterable-fields";
     pub const SORTABLE_FIELDS_KEY: &str = "sortable-fields";
     pub const FIELD_DISTRIBUTION_KEY: &str = "fields-distribution";
     pub const FIELDS_IDS_MAP_KEY: &str = "fields-ids-map";
     pub const HARD_EXTERNAL_DOCUMENTS_IDS_KEY: &str = "hard-external-documents-ids";
+    pub const LANGUAGE_DOCUMENTS_IDS_PREFIX: &str = "language-documents-ids-";
+    pub const LANGUAGE_STOP_WORDS_PREFIX: &str = "language-stop-words-";
+    pub const MAX_FACET_VALUE_LENGTH_KEY: &str = "max-facet-value-length";
+    pub const MAX_NGRAM_KEY: &str = "max-ngram";
+    pub const MAX_PROXIMITY_DISTANCE_KEY: &str = "max-proximity-distance";
     pub const NUMBER_FACETED_DOCUMENTS_IDS_PREFIX: &str = "number-faceted-documents-ids";
     pub const PRIMARY_KEY_KEY: &str = "primary-key";
+    pub const PRIMARY_KEY_POLICY_KEY: &str = "primary-key-policy";
     pub const SEARCHABLE_FIELDS_KEY: &str = "searchable-fields";
     pub const SOFT_EXTERNAL_DOCUMENTS_IDS_KEY: &str = "soft-external-documents-ids";
     pub const STOP_WORDS_KEY: &str = "stop-words";
@@ -62,6 +79,10 @@ pub mod db_name {
     pub const DOCUMENTS: &str = "documents";
 }
 
+/// The number of databases that can be created with [`Index::create_derived_database`],
+/// in addition to the databases of the index itself.
+pub const MAX_DERIVED_DATABASES: u32 = 4;
+
 #[derive(Clone)]
 pub struct Index {
     /// The LMDB environment which this index is associated with.
@@ -108,7 +129,7 @@ impl Index {
     pub fn new<P: AsRef<Path>>(mut options: heed::EnvOpenOptions, path: P) -> Result<Index> {
         use db_name::*;
 
-        options.max_dbs(14);
+        options.max_dbs(14 + MAX_DERIVED_DATABASES);
         unsafe { options.flag(Flags::MdbAlwaysFreePages) };
 
         let env = options.open(path)?;
@@ -178,6 +199,18 @@ impl Index {
         self.env.path()
     }
 
+    /// Creates, or opens if it already exists, a database owned by the application in the
+    /// environment of this index. It can be filled from the indexed documents words with
+    /// [`IndexDocuments::derived_database`].
+    ///
+    /// [`IndexDocuments::derived_database`]: crate::update::IndexDocuments::derived_database
+    ///
+    /// The name must not be one of the index databases names and no write transaction
+    /// must be alive when calling this method.
+    pub fn create_derived_database(&self, name: &str) -> Result<PolyDatabase> {
+        Ok(self.env.create_poly_database(Some(name))?)
+    }
+
     /// Returns an `EnvClosingEvent` that can be used to wait for the closing event,
     /// multiple threads can wait on this event.
     ///
@@ -187,6 +220,46 @@ impl Index {
         self.env.prepare_for_closing()
     }
 
+    /// Closes the environment and reopens it with the given map size, the data is kept.
+    ///
+    /// This is how an index can be grown when the [`MaxDatabaseSizeReached`] error is
+    /// returned. The index must not be used anywhere else, all the copies of this `Index`
+    /// must be dropped first, otherwise an [`IndexInUse`] error is returned.
+    ///
+    /// Note that the environment is prepared for closing before the other copies are
+    /// detected: when an [`IndexInUse`] error is returned the other copies can still be
+    /// used, but the index can't be opened again with [`Index::new`] until all of them
+    /// are dropped, the last one can still be grown with this method.
+    ///
+    /// [`MaxDatabaseSizeReached`]: UserError::MaxDatabaseSizeReached
+    /// [`IndexInUse`]: UserError::IndexInUse
+    pub fn grow_map_size(self, map_size: usize) -> Result<Index> {
+        let path = self.path().to_path_buf();
+
+        // The environment is closed right away if this index was the last reference to it.
+        let closing_event = self.prepare_for_closing();
+        if !closing_event.wait_timeout(Duration::from_secs(0)) {
+            return Err(UserError::IndexInUse.into());
+        }
+
+        let mut options = heed::EnvOpenOptions::new();
+        options.map_size(map_size);
+        Index::new(options, path)
+    }
+
+    /// Writes a compacted copy of the environment into the `data.mdb` file of the given
+    /// directory, the space freed by the deleted documents is reclaimed in the copy.
+    ///
+    /// The copy can then be opened with [`Index::new`] in place of this index. Enough disk
+    /// space must be available for the copy, and a read transaction is held during it,
+    /// preventing the pages freed in the meantime from being reused.
+    pub fn compact_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path)?;
+        self.env.copy_to_path(path.join("data.mdb"), CompactionOption::Enabled)?;
+        Ok(())
+    }
+
     /* documents ids */
 
     /// Writes the documents ids that corresponds to the user-ids-documents-ids FST.
@@ -213,6 +286,11 @@ impl Index {
         Ok(count.unwrap_or_default())
     }
 
+    /// Returns `true` if there is no document in the database.
+    pub fn is_empty(&self, rtxn: &RoTxn) -> Result<bool> {
+        self.number_of_documents(rtxn).map(|count| count == 0)
+    }
+
     /* primary key */
 
     /// Writes the documents primary key, this is the field name that is used to store the id.
@@ -231,6 +309,49 @@ impl Index {
         self.main.get::<_, Str, Str>(rtxn, main_key::PRIMARY_KEY_KEY)
     }
 
+    /// Returns the field id of the documents primary key, `None` if it hasn't been defined.
+    pub fn primary_key_field_id(&self, rtxn: &RoTxn) -> Result<Option<FieldId>> {
+        match self.primary_key(rtxn)? {
+            Some(primary_key) => {
+                let fields_ids_map = self.fields_ids_map(rtxn)?;
+                let field_id = fields_ids_map.id(primary_key).ok_or_else(|| {
+                    FieldIdMapMissingEntry::FieldName {
+                        field_name: primary_key.to_string(),
+                        process: "primary_key_field_id",
+                    }
+                })?;
+                Ok(Some(field_id))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /* primary key policy */
+
+    pub(crate) fn put_primary_key_policy(
+        &self,
+        wtxn: &mut RwTxn,
+        policy: &PrimaryKeyPolicy,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, SerdeJson<PrimaryKeyPolicy>>(
+            wtxn,
+            main_key::PRIMARY_KEY_POLICY_KEY,
+            policy,
+        )
+    }
+
+    /// Returns how the primary key values are converted into document ids.
+    pub fn primary_key_policy(&self, rtxn: &RoTxn) -> heed::Result<PrimaryKeyPolicy> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<PrimaryKeyPolicy>>(rtxn, main_key::PRIMARY_KEY_POLICY_KEY)?
+            .unwrap_or_default())
+    }
+
+    pub(crate) fn delete_primary_key_policy(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::PRIMARY_KEY_POLICY_KEY)
+    }
+
     /* external documents ids */
 
     /// Writes the external documents ids and internal ids (i.e. `u32`).
@@ -273,6 +394,15 @@ impl Index {
         Ok(ExternalDocumentsIds::new(hard, soft))
     }
 
+    /// Returns a lazy iterator over all the external documents ids along with their internal
+    /// ids, in the external ids order, without reading the documents.
+    pub fn external_ids_stream<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+    ) -> Result<ExternalDocumentsIdsStream<'t>> {
+        Ok(self.external_documents_ids(rtxn)?.into_stream())
+    }
+
     /* fields ids map */
 
     /// Writes the fields ids map which associate the documents keys with an internal field id
@@ -344,7 +474,10 @@ impl Index {
     /// Returns the displayed fields in the order they were set by the user. If it returns
     /// `None` it means that all the attributes are set as displayed in the order of the `FieldsIdsMap`.
     pub fn displayed_fields<'t>(&self, rtxn: &'t RoTxn) -> heed::Result<Option<Vec<&'t str>>> {
-        self.main.get::<_, Str, SerdeBincode<Vec<&'t str>>>(rtxn, main_key::DISPLAYED_FIELDS_KEY)
+        let fields = self
+            .main
+            .get::<_, Str, SerdeBincode<Vec<&'t str>>>(rtxn, main_key::DISPLAYED_FIELDS_KEY)?;
+        Ok(fields.map(without_ranking_score))
     }
 
     /// Identical to `displayed_fields`, but returns the ids instead.
@@ -387,7 +520,10 @@ impl Index {
     /// Returns the searchable fields, those are the fields that are indexed,
     /// if the searchable fields aren't there it means that **all** the fields are indexed.
     pub fn searchable_fields<'t>(&self, rtxn: &'t RoTxn) -> heed::Result<Option<Vec<&'t str>>> {
-        self.main.get::<_, Str, SerdeBincode<Vec<&'t str>>>(rtxn, main_key::SEARCHABLE_FIELDS_KEY)
+        let fields = self
+            .main
+            .get::<_, Str, SerdeBincode<Vec<&'t str>>>(rtxn, main_key::SEARCHABLE_FIELDS_KEY)?;
+        Ok(fields.map(without_ranking_score))
     }
 
     /// Identical to `searchable_fields`, but returns the ids instead.
@@ -481,7 +617,8 @@ impl Index {
 
     /// Returns the faceted fields names.
     ///
-    /// Faceted fields are the union of all the filterable, sortable, distinct, and Asc/Desc fields.
+    /// Faceted fields are the union of all the filterable, sortable, distinct, and Asc/Desc fields,
+    /// the reserved `_rankingScore` field is always faceted.
     pub fn faceted_fields(&self, rtxn: &RoTxn) -> Result<HashSet<String>> {
         let filterable_fields = self.filterable_fields(rtxn)?;
         let sortable_fields = self.sortable_fields(rtxn)?;
@@ -495,6 +632,7 @@ impl Index {
         let mut faceted_fields = filterable_fields;
         faceted_fields.extend(sortable_fields);
         faceted_fields.extend(asc_desc_fields);
+        faceted_fields.insert(RANKING_SCORE_FIELD.to_owned());
         if let Some(field) = distinct_field {
             faceted_fields.insert(field.to_owned());
         }
@@ -587,6 +725,24 @@ impl Index {
         }
     }
 
+    /// Deletes the documents ids that are faceted with numbers and strings under this field id.
+    pub(crate) fn delete_faceted_documents_ids(
+        &self,
+        wtxn: &mut RwTxn,
+        field_id: FieldId,
+    ) -> heed::Result<()> {
+        for prefix in &[
+            main_key::NUMBER_FACETED_DOCUMENTS_IDS_PREFIX,
+            main_key::STRING_FACETED_DOCUMENTS_IDS_PREFIX,
+        ] {
+            let mut buffer = Vec::with_capacity(prefix.len() + size_of::<FieldId>());
+            buffer.extend_from_slice(prefix.as_bytes());
+            buffer.extend_from_slice(&field_id.to_be_bytes());
+            self.main.delete::<_, ByteSlice>(wtxn, &buffer)?;
+        }
+        Ok(())
+    }
+
     /* distinct field */
 
     pub(crate) fn put_distinct_field(
@@ -605,6 +761,127 @@ impl Index {
         self.main.delete::<_, Str>(wtxn, main_key::DISTINCT_FIELD_KEY)
     }
 
+    /* exactness prefix tier */
+
+    pub(crate) fn put_exactness_prefix_tier(
+        &self,
+        wtxn: &mut RwTxn,
+        prefix_tier: bool,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, SerdeJson<bool>>(
+            wtxn,
+            main_key::EXACTNESS_PREFIX_TIER_KEY,
+            &prefix_tier,
+        )
+    }
+
+    /// Returns whether the exactness criterion ranks the documents matching the query words
+    /// as prefixes between the ones matching them exactly and the ones containing typos.
+    pub fn exactness_prefix_tier(&self, rtxn: &RoTxn) -> heed::Result<bool> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<bool>>(rtxn, main_key::EXACTNESS_PREFIX_TIER_KEY)?
+            .unwrap_or(false))
+    }
+
+    pub(crate) fn delete_exactness_prefix_tier(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::EXACTNESS_PREFIX_TIER_KEY)
+    }
+
+    /* max facet value length */
+
+    pub(crate) fn put_max_facet_value_length(
+        &self,
+        wtxn: &mut RwTxn,
+        length: usize,
+    ) -> heed::Result<()> {
+        let length = length as u64;
+        self.main.put::<_, Str, OwnedType<u64>>(wtxn, main_key::MAX_FACET_VALUE_LENGTH_KEY, &length)
+    }
+
+    /// Returns the maximum number of characters of the facet strings, if any,
+    /// the longer facet strings are truncated when indexed.
+    pub fn max_facet_value_length(&self, rtxn: &RoTxn) -> heed::Result<Option<usize>> {
+        Ok(self
+            .main
+            .get::<_, Str, OwnedType<u64>>(rtxn, main_key::MAX_FACET_VALUE_LENGTH_KEY)?
+            .map(|length| length as usize))
+    }
+
+    pub(crate) fn delete_max_facet_value_length(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::MAX_FACET_VALUE_LENGTH_KEY)
+    }
+
+    /* date fields */
+
+    pub(crate) fn put_date_fields(
+        &self,
+        wtxn: &mut RwTxn,
+        fields: &HashSet<String>,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::DATE_FIELDS_KEY, fields)
+    }
+
+    /// Returns the names of the faceted fields whose string values are dates,
+    /// these values are indexed as facet numbers of milliseconds since the epoch.
+    pub fn date_fields(&self, rtxn: &RoTxn) -> heed::Result<HashSet<String>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<_>>(rtxn, main_key::DATE_FIELDS_KEY)?
+            .unwrap_or_default())
+    }
+
+    pub(crate) fn delete_date_fields(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::DATE_FIELDS_KEY)
+    }
+
+    /* max ngram */
+
+    pub(crate) fn put_max_ngram(&self, wtxn: &mut RwTxn, max_ngram: usize) -> heed::Result<()> {
+        let max_ngram = max_ngram as u64;
+        self.main.put::<_, Str, OwnedType<u64>>(wtxn, main_key::MAX_NGRAM_KEY, &max_ngram)
+    }
+
+    /// Returns the maximum number of consecutive words that are concatenated and indexed
+    /// as a single word, `None` when the ngram indexing is disabled.
+    pub fn max_ngram(&self, rtxn: &RoTxn) -> heed::Result<Option<usize>> {
+        Ok(self
+            .main
+            .get::<_, Str, OwnedType<u64>>(rtxn, main_key::MAX_NGRAM_KEY)?
+            .map(|max_ngram| max_ngram as usize))
+    }
+
+    pub(crate) fn delete_max_ngram(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::MAX_NGRAM_KEY)
+    }
+
+    /* max proximity distance */
+
+    pub(crate) fn put_max_proximity_distance(
+        &self,
+        wtxn: &mut RwTxn,
+        distance: u8,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<u8>>(
+            wtxn,
+            main_key::MAX_PROXIMITY_DISTANCE_KEY,
+            &distance,
+        )
+    }
+
+    /// Returns the maximum proximity of the word pairs stored in the proximity database,
+    /// the farther pairs are not recorded.
+    pub fn max_proximity_distance(&self, rtxn: &RoTxn) -> heed::Result<u8> {
+        Ok(self
+            .main
+            .get::<_, Str, OwnedType<u8>>(rtxn, main_key::MAX_PROXIMITY_DISTANCE_KEY)?
+            .unwrap_or(DEFAULT_MAX_PROXIMITY_DISTANCE))
+    }
+
+    pub(crate) fn delete_max_proximity_distance(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::MAX_PROXIMITY_DISTANCE_KEY)
+    }
+
     /* criteria */
 
     pub(crate) fn put_criteria(
@@ -666,6 +943,85 @@ impl Index {
         }
     }
 
+    /* language stop words */
+
+    pub(crate) fn put_language_stop_words<A: AsRef<[u8]>>(
+        &self,
+        wtxn: &mut RwTxn,
+        language: &str,
+        fst: &fst::Set<A>,
+    ) -> heed::Result<()> {
+        let key = format!("{}{}", main_key::LANGUAGE_STOP_WORDS_PREFIX, language);
+        self.main.put::<_, Str, ByteSlice>(wtxn, &key, fst.as_fst().as_bytes())
+    }
+
+    pub(crate) fn delete_language_stop_words(
+        &self,
+        wtxn: &mut RwTxn,
+        language: &str,
+    ) -> heed::Result<bool> {
+        let key = format!("{}{}", main_key::LANGUAGE_STOP_WORDS_PREFIX, language);
+        self.main.delete::<_, Str>(wtxn, &key)
+    }
+
+    /// Returns the stop words registered for the given language.
+    pub fn language_stop_words<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+        language: &str,
+    ) -> Result<Option<fst::Set<&'t [u8]>>> {
+        let key = format!("{}{}", main_key::LANGUAGE_STOP_WORDS_PREFIX, language);
+        match self.main.get::<_, Str, ByteSlice>(rtxn, &key)? {
+            Some(bytes) => Ok(Some(fst::Set::new(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the stop words of the given language, falling back to the global stop words
+    /// when no language is given or when no stop words are registered for this language.
+    pub fn stop_words_for_language<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+        language: Option<&str>,
+    ) -> Result<Option<fst::Set<&'t [u8]>>> {
+        if let Some(language) = language {
+            if let Some(stop_words) = self.language_stop_words(rtxn, language)? {
+                return Ok(Some(stop_words));
+            }
+        }
+        self.stop_words(rtxn)
+    }
+
+    /* language documents ids */
+
+    /// Writes the documents ids that have been indexed with this language hint,
+    /// the entry is removed when there are no more such documents.
+    pub(crate) fn put_language_documents_ids(
+        &self,
+        wtxn: &mut RwTxn,
+        language: &str,
+        docids: &RoaringBitmap,
+    ) -> heed::Result<()> {
+        let key = format!("{}{}", main_key::LANGUAGE_DOCUMENTS_IDS_PREFIX, language);
+        if docids.is_empty() {
+            self.main.delete::<_, Str>(wtxn, &key).map(drop)
+        } else {
+            self.main.put::<_, Str, RoaringBitmapCodec>(wtxn, &key, docids)
+        }
+    }
+
+    /// Returns the documents ids that have been indexed with a language hint, by language.
+    pub fn languages_documents_ids(
+        &self,
+        rtxn: &RoTxn,
+    ) -> heed::Result<BTreeMap<String, RoaringBitmap>> {
+        let prefix = main_key::LANGUAGE_DOCUMENTS_IDS_PREFIX;
+        self.main
+            .prefix_iter::<_, Str, RoaringBitmapCodec>(rtxn, prefix)?
+            .map(|result| result.map(|(key, docids)| (key[prefix.len()..].to_string(), docids)))
+            .collect()
+    }
+
     /* synonyms */
 
     pub(crate) fn put_synonyms(
@@ -719,6 +1075,197 @@ impl Index {
         }
     }
 
+    /* rename field */
+
+    /// Renames a field in the fields ids map, the field distribution and in the settings
+    /// referencing it, the field id is kept and the documents and the databases, that only
+    /// store the field id, are left untouched, there is no need to reindex.
+    ///
+    /// The nested faceted fields, the dotted paths into the field like `old.path`, are
+    /// renamed along with it.
+    ///
+    /// Returns `false` if the field was unknown, the new name and the renamed nested fields
+    /// must not be used by any other field.
+    pub fn rename_field(&self, wtxn: &mut RwTxn, old_name: &str, new_name: &str) -> Result<bool> {
+        let mut fields_ids_map = self.fields_ids_map(wtxn)?;
+        if fields_ids_map.id(new_name).is_some() {
+            return Err(UserError::FieldAlreadyExists { field_name: new_name.to_string() }.into());
+        }
+        if fields_ids_map.id(old_name).is_none() {
+            return Ok(false);
+        }
+
+        let rename = |name: &str| match name.strip_prefix(old_name) {
+            Some("") => new_name.to_string(),
+            Some(path) if path.starts_with('.') => format!("{}{}", new_name, path),
+            _ => name.to_string(),
+        };
+        let is_renamed = |name: &str| rename(name) != name;
+
+        let names: Vec<_> =
+            fields_ids_map.names().filter(|name| is_renamed(name)).map(String::from).collect();
+        // The nested fields must not collide with the fields that are kept as is.
+        if let Some(name) = names
+            .iter()
+            .map(|name| rename(name))
+            .find(|name| fields_ids_map.id(name).is_some() && !is_renamed(name))
+        {
+            return Err(UserError::FieldAlreadyExists { field_name: name }.into());
+        }
+        for name in names {
+            let renamed = rename(&name);
+            if fields_ids_map.rename(&name, &renamed).is_none() {
+                return Err(UserError::FieldAlreadyExists { field_name: renamed }.into());
+            }
+        }
+        self.put_fields_ids_map(wtxn, &fields_ids_map)?;
+
+        let field_distribution = self.field_distribution(wtxn)?;
+        if field_distribution.keys().any(|name| is_renamed(name)) {
+            let field_distribution: FieldDistribution = field_distribution
+                .into_iter()
+                .map(|(name, count)| (rename(&name), count))
+                .collect();
+            self.put_field_distribution(wtxn, &field_distribution)?;
+        }
+
+        if self.primary_key(wtxn)? == Some(old_name) {
+            self.put_primary_key(wtxn, new_name)?;
+        }
+
+        if let Some(fields) = self.displayed_fields(wtxn)? {
+            let fields: Vec<_> = fields.into_iter().map(rename).collect();
+            let fields: Vec<_> = fields.iter().map(String::as_str).collect();
+            self.put_displayed_fields(wtxn, &fields)?;
+        }
+
+        if let Some(fields) = self.searchable_fields(wtxn)? {
+            let fields: Vec<_> = fields.into_iter().map(rename).collect();
+            let fields: Vec<_> = fields.iter().map(String::as_str).collect();
+            self.put_searchable_fields(wtxn, &fields)?;
+        }
+
+        let fields = self.filterable_fields(wtxn)?;
+        if fields.iter().any(|name| is_renamed(name)) {
+            let fields: HashSet<_> = fields.iter().map(|name| rename(name)).collect();
+            self.put_filterable_fields(wtxn, &fields)?;
+        }
+
+        let fields = self.sortable_fields(wtxn)?;
+        if fields.iter().any(|name| is_renamed(name)) {
+            let fields: HashSet<_> = fields.iter().map(|name| rename(name)).collect();
+            self.put_sortable_fields(wtxn, &fields)?;
+        }
+
+        let fields = self.date_fields(wtxn)?;
+        if fields.iter().any(|name| is_renamed(name)) {
+            let fields: HashSet<_> = fields.iter().map(|name| rename(name)).collect();
+            self.put_date_fields(wtxn, &fields)?;
+        }
+
+        if self.distinct_field(wtxn)? == Some(old_name) {
+            self.put_distinct_field(wtxn, new_name)?;
+        }
+
+        let criteria = self.criteria(wtxn)?;
+        let is_sorted_by = |criterion: &Criterion| match criterion {
+            Criterion::Asc(field) | Criterion::Desc(field) => field == old_name,
+            _otherwise => false,
+        };
+        if criteria.iter().any(is_sorted_by) {
+            let criteria: Vec<_> = criteria
+                .into_iter()
+                .map(|criterion| match criterion {
+                    Criterion::Asc(field) => Criterion::Asc(rename(&field)),
+                    Criterion::Desc(field) => Criterion::Desc(rename(&field)),
+                    otherwise => otherwise,
+                })
+                .collect();
+            self.put_criteria(wtxn, &criteria)?;
+        }
+
+        Ok(true)
+    }
+
+    /* remove field */
+
+    /// Removes the facet values of a field and its faceted documents ids, the documents,
+    /// the words and the field itself are kept, the field remains searchable.
+    pub fn remove_field_facets(&self, wtxn: &mut RwTxn, field_id: FieldId) -> Result<()> {
+        // All the facet databases are prefixed by the field id.
+        let prefix = field_id.to_be_bytes();
+        let databases = [
+            self.facet_id_f64_docids.remap_types::<ByteSlice, DecodeIgnore>(),
+            self.facet_id_string_docids.remap_types::<ByteSlice, DecodeIgnore>(),
+            self.field_id_docid_facet_f64s.remap_types::<ByteSlice, DecodeIgnore>(),
+            self.field_id_docid_facet_strings.remap_types::<ByteSlice, DecodeIgnore>(),
+        ];
+        for database in &databases {
+            let mut iter = database.prefix_iter_mut(wtxn, &prefix[..])?;
+            while iter.next().transpose()?.is_some() {
+                // safety: we don't keep references from inside the LMDB database.
+                unsafe { iter.del_current()? };
+            }
+        }
+        self.delete_faceted_documents_ids(wtxn, field_id)?;
+
+        Ok(())
+    }
+
+    /// Removes a field from all the documents, its facet values and its word count entries,
+    /// and forgets about it in the field distribution and in the fields ids map.
+    ///
+    /// The words of this field are kept in the words databases until the next reindexing.
+    /// Returns `false` if the field was unknown, the primary key cannot be removed.
+    pub fn remove_field(&self, wtxn: &mut RwTxn, field_name: &str) -> Result<bool> {
+        if self.primary_key(wtxn)? == Some(field_name) {
+            return Err(UserError::PrimaryKeyCannotBeChanged.into());
+        }
+
+        let mut fields_ids_map = self.fields_ids_map(wtxn)?;
+        let field_id = match fields_ids_map.remove(field_name) {
+            Some(field_id) => field_id,
+            None => return Ok(false),
+        };
+
+        // We rewrite the documents that contain this field, without it.
+        let mut buffer = Vec::new();
+        let mut iter = self.documents.remap_data_type::<ByteSlice>().iter_mut(wtxn)?;
+        while let Some(result) = iter.next() {
+            let (docid, bytes) = result?;
+            let obkv = obkv::KvReaderU16::new(bytes);
+            if obkv.get(field_id).is_some() {
+                buffer.clear();
+                let mut writer = obkv::KvWriter::<_, FieldId>::new(&mut buffer);
+                for (fid, value) in obkv.iter().filter(|(fid, _)| *fid != field_id) {
+                    writer.insert(fid, value)?;
+                }
+                writer.finish()?;
+                // safety: we don't keep references from inside the LMDB database.
+                unsafe { iter.put_current(&docid, &buffer)? };
+            }
+        }
+        drop(iter);
+
+        // The word count database is prefixed by the field id.
+        let prefix = field_id.to_be_bytes();
+        let database = self.field_id_word_count_docids.remap_types::<ByteSlice, DecodeIgnore>();
+        let mut iter = database.prefix_iter_mut(wtxn, &prefix[..])?;
+        while iter.next().transpose()?.is_some() {
+            // safety: we don't keep references from inside the LMDB database.
+            unsafe { iter.del_current()? };
+        }
+        drop(iter);
+        self.remove_field_facets(wtxn, field_id)?;
+
+        let mut field_distribution = self.field_distribution(wtxn)?;
+        field_distribution.remove(field_name);
+        self.put_field_distribution(wtxn, &field_distribution)?;
+        self.put_fields_ids_map(wtxn, &fields_ids_map)?;
+
+        Ok(true)
+    }
+
     /* word documents count */
 
     /// Returns the number of documents ids associated with the given word,
@@ -748,6 +1295,93 @@ impl Index {
         Ok(documents)
     }
 
+    /// Returns the requested documents by their external ids, in the order of the ids,
+    /// the document is `None` when the external id is unknown.
+    ///
+    /// The external documents ids are only fetched once for all the given ids.
+    pub fn documents_by_external_ids<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+        ids: &[&str],
+    ) -> Result<Vec<(String, Option<obkv::KvReaderU16<'t>>)>> {
+        let external_documents_ids = self.external_documents_ids(rtxn)?;
+        let mut documents = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            let document = match external_documents_ids.get(id) {
+                Some(docid) => self.documents.get(rtxn, &BEU32::new(docid))?,
+                None => None,
+            };
+            documents.push((id.to_string(), document));
+        }
+
+        Ok(documents)
+    }
+
+    /// Returns the requested documents converted into JSON objects, in the order of the ids.
+    ///
+    /// Only the displayed fields are returned, further restricted to the
+    /// `projection` fields when specified. Returns an error if a document is missing.
+    pub fn documents_json(
+        &self,
+        rtxn: &RoTxn,
+        ids: impl IntoIterator<Item = DocumentId>,
+        projection: Option<&HashSet<FieldId>>,
+    ) -> Result<Vec<Value>> {
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let displayed_fields = match self.displayed_fields_ids(rtxn)? {
+            Some(fields) => fields,
+            None => fields_ids_map.ids().collect(),
+        };
+        let fields: Vec<_> = match projection {
+            Some(projection) => {
+                displayed_fields.into_iter().filter(|id| projection.contains(id)).collect()
+            }
+            None => displayed_fields,
+        };
+
+        self.documents(rtxn, ids)?
+            .into_iter()
+            .map(|(_id, obkv)| obkv_to_json(&fields, &fields_ids_map, obkv).map(Value::Object))
+            .collect()
+    }
+
+    /// Returns an iterator over the documents that have at least one value of the given
+    /// filterable field strictly greater than the `threshold`, in the internal ids order.
+    ///
+    /// This is useful to only fetch the documents updated since a given instant when the
+    /// documents store a numeric timestamp, the field must be declared filterable.
+    pub fn documents_where_number_field_gt<'t>(
+        &'t self,
+        rtxn: &'t RoTxn,
+        field_name: &str,
+        threshold: f64,
+    ) -> Result<impl Iterator<Item = Result<(DocumentId, obkv::KvReaderU16<'t>)>> + 't> {
+        let mut documents_ids = RoaringBitmap::new();
+        if let Some(field_id) = self.fields_ids_map(rtxn)?.id(field_name) {
+            let iter = self
+                .field_id_docid_facet_f64s
+                .remap_key_type::<ByteSlice>()
+                .prefix_iter(rtxn, &field_id.to_be_bytes())?
+                .remap_key_type::<FieldDocIdFacetF64Codec>();
+
+            for result in iter {
+                let ((_, docid, value), ()) = result?;
+                if value > threshold {
+                    documents_ids.insert(docid);
+                }
+            }
+        }
+
+        Ok(documents_ids.into_iter().map(move |id| {
+            let kv = self
+                .documents
+                .get(rtxn, &BEU32::new(id))?
+                .ok_or_else(|| UserError::UnknownInternalDocumentId { document_id: id })?;
+            Ok((id, kv))
+        }))
+    }
+
     /// Returns an iterator over all the documents in the index.
     pub fn all_documents<'t>(
         &self,
@@ -760,16 +1394,223 @@ impl Index {
             .map(|document| document.map(|(id, obkv)| (id.get(), obkv))))
     }
 
+    /// Returns a hash of the documents and the settings of the index.
+    ///
+    /// The fingerprint only depends on the fields names and values of the documents, it is
+    /// therefore the same for two indexes built with the same documents in a different order.
+    /// A fixed hash algorithm is used, unlike the `DefaultHasher` one it doesn't change between
+    /// Rust releases, the fingerprint can be stored and compared with one computed later.
+    pub fn content_fingerprint(&self, rtxn: &RoTxn) -> Result<u64> {
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+
+        let mut documents_hashes = Vec::new();
+        for result in self.all_documents(rtxn)? {
+            let (_id, obkv) = result?;
+            let mut fields = Vec::new();
+            for (id, value) in obkv.iter() {
+                let name = fields_ids_map.name(id).ok_or(FieldIdMapMissingEntry::FieldId {
+                    field_id: id,
+                    process: "content_fingerprint",
+                })?;
+                fields.push((name, value));
+            }
+            fields.sort_unstable();
+
+            let mut hasher = FxHasher64::default();
+            fields.hash(&mut hasher);
+            documents_hashes.push(hasher.finish());
+        }
+        documents_hashes.sort_unstable();
+
+        let mut hasher = FxHasher64::default();
+        documents_hashes.hash(&mut hasher);
+
+        self.primary_key(rtxn)?.hash(&mut hasher);
+        self.displayed_fields(rtxn)?.hash(&mut hasher);
+        self.searchable_fields(rtxn)?.hash(&mut hasher);
+        self.filterable_fields(rtxn)?.into_iter().collect::<BTreeSet<_>>().hash(&mut hasher);
+        self.sortable_fields(rtxn)?.into_iter().collect::<BTreeSet<_>>().hash(&mut hasher);
+        self.distinct_field(rtxn)?.hash(&mut hasher);
+        self.criteria(rtxn)?.iter().map(ToString::to_string).for_each(|c| c.hash(&mut hasher));
+        self.synonyms(rtxn)?.into_iter().collect::<BTreeMap<_, _>>().hash(&mut hasher);
+        self.stop_words(rtxn)?.map(|set| set.stream().into_bytes()).hash(&mut hasher);
+
+        Ok(hasher.finish())
+    }
+
     pub fn facets_distribution<'a>(&'a self, rtxn: &'a RoTxn) -> FacetDistribution<'a> {
         FacetDistribution::new(rtxn, self)
     }
 
-    pub fn search<'a>(&'a self, rtxn: &'a RoTxn) -> Search<'a> {
-        Search::new(rtxn, self)
-    }
+    /// Returns the facet values distribution of the given fields over all the documents.
+    ///
+    /// The pre-aggregated facet databases are directly read, without intersecting them
+    /// with a set of candidates, which makes it the cheapest way to compute it.
+    pub fn global_facet_distribution<I, A>(
+        &self,
+        rtxn: &RoTxn,
+        fields: I,
+    ) -> Result<BTreeMap<String, BTreeMap<String, u64>>>
+    where
+        I: IntoIterator<Item = A>,
+        A: AsRef<str>,
+    {
+        FacetDistribution::new(rtxn, self).facets(fields).execute()
+    }
+
+    /// Returns a lazy iterator over the facet values of the given field along with the
+    /// number of candidates associated with each of them, the values that are not associated
+    /// with any candidate are skipped. All the documents are considered when no candidates
+    /// are specified.
+    ///
+    /// The strings are yielded first and then the numbers, in the facet values order. Unlike
+    /// [`FacetDistribution`] the distribution is never materialized, the caller can stop
+    /// whenever it wants, to only retrieve the first values of a huge facet for example.
+    pub fn facet_distribution_stream<'t>(
+        &'t self,
+        rtxn: &'t RoTxn,
+        field_name: &str,
+        candidates: Option<&'t RoaringBitmap>,
+    ) -> Result<impl Iterator<Item = Result<(FacetValue, u64)>> + 't> {
+        let field_id = match self.fields_ids_map(rtxn)?.id(field_name) {
+            Some(field_id) => field_id,
+            None => return Ok(Either::Left(iter::empty())),
+        };
 
-    /// Returns the index creation time.
-    pub fn created_at(&self, rtxn: &RoTxn) -> Result<DateTime<Utc>> {
+        let mut prefix = field_id.to_be_bytes().to_vec();
+        prefix.push(0); // the level zero
+
+        let strings = self
+            .facet_id_string_docids
+            .remap_key_type::<ByteSlice>()
+            .prefix_iter(rtxn, &prefix)?
+            .remap_key_type::<FacetStringLevelZeroCodec>()
+            .map(|result| {
+                result.map(|((_, _), (original, docids))| (FacetValue::from(original), docids))
+            });
+
+        let numbers = self
+            .facet_id_f64_docids
+            .remap_key_type::<ByteSlice>()
+            .prefix_iter(rtxn, &prefix)?
+            .remap_key_type::<FacetLevelValueF64Codec>()
+            .map(|result| {
+                result.map(|((_, _, value, _), docids)| (FacetValue::from(value), docids))
+            });
+
+        let iter = strings.chain(numbers).filter_map(move |result| match result {
+            Ok((value, mut docids)) => {
+                if let Some(candidates) = candidates {
+                    docids &= candidates;
+                }
+                if docids.is_empty() {
+                    None
+                } else {
+                    Some(Ok((value, docids.len())))
+                }
+            }
+            Err(error) => Some(Err(error.into())),
+        });
+
+        Ok(Either::Right(iter))
+    }
+
+    /// Returns an iterator over all the facet values of the given field along with the
+    /// number of documents associated with each of them, in the given order.
+    pub fn facet_values<'t>(
+        &'t self,
+        rtxn: &'t RoTxn,
+        field_name: &str,
+        order: FacetValueOrder,
+    ) -> Result<impl Iterator<Item = Result<(FacetValue, u64)>> + 't> {
+        let iter = self.facet_distribution_stream(rtxn, field_name, None)?;
+        match order {
+            FacetValueOrder::ByValue => Ok(Either::Left(iter)),
+            FacetValueOrder::ByCount => {
+                let mut values: Vec<_> = iter.collect::<Result<_>>()?;
+                // The sort is stable, values with the same count stay in the storage order.
+                values.sort_by(|(_, a), (_, b)| b.cmp(a));
+                Ok(Either::Right(values.into_iter().map(Ok)))
+            }
+        }
+    }
+
+    /// Returns the facet strings of the given field that start with the query, along with
+    /// the number of candidates associated with each of them, by descending count. All the
+    /// documents are considered when no candidates are specified.
+    ///
+    /// The query is normalized like the facet strings, the original values are returned.
+    pub fn facet_values_search(
+        &self,
+        rtxn: &RoTxn,
+        field_name: &str,
+        query: &str,
+        limit: usize,
+        candidates: Option<&RoaringBitmap>,
+    ) -> Result<Vec<(String, u64)>> {
+        let field_id = match self.fields_ids_map(rtxn)?.id(field_name) {
+            Some(field_id) => field_id,
+            None => return Ok(Vec::new()),
+        };
+
+        let query = query.trim().to_lowercase();
+        let mut prefix = Vec::new();
+        FacetStringLevelZeroCodec::serialize_into(field_id, &query, &mut prefix);
+
+        let iter = self
+            .facet_id_string_docids
+            .remap_key_type::<ByteSlice>()
+            .prefix_iter(rtxn, &prefix)?
+            .remap_key_type::<FacetStringLevelZeroCodec>();
+
+        let mut values = Vec::new();
+        for result in iter {
+            let ((_, _), (original, mut docids)) = result?;
+            if let Some(candidates) = candidates {
+                docids &= candidates;
+            }
+            if !docids.is_empty() {
+                values.push((original.to_string(), docids.len()));
+            }
+        }
+
+        // The sort is stable, values with the same count stay in the storage order.
+        values.sort_by(|(_, a), (_, b)| b.cmp(a));
+        values.truncate(limit);
+
+        Ok(values)
+    }
+
+    /// Returns the facet distributions of all the given fields, the values that are not
+    /// associated with any candidate are skipped. All the documents are considered when
+    /// no candidates are specified.
+    ///
+    /// When there is a small amount of candidates they are visited only once for all the
+    /// fields, otherwise the facet databases of each field are iterated one after the other.
+    pub fn facet_distributions(
+        &self,
+        rtxn: &RoTxn,
+        fields: &[FieldId],
+        candidates: Option<&RoaringBitmap>,
+    ) -> Result<HashMap<FieldId, HashMap<String, u64>>> {
+        let mut facet_distribution = self.facets_distribution(rtxn);
+        if let Some(candidates) = candidates {
+            facet_distribution.candidates(candidates.clone());
+        }
+
+        let distributions = facet_distribution.execute_fields_ids(fields)?;
+        Ok(distributions
+            .into_iter()
+            .map(|(field_id, distribution)| (field_id, distribution.into_iter().collect()))
+            .collect())
+    }
+
+    pub fn search<'a>(&'a self, rtxn: &'a RoTxn) -> Search<'a> {
+        Search::new(rtxn, self)
+    }
+
+    /// Returns the index creation time.
+    pub fn created_at(&self, rtxn: &RoTxn) -> Result<DateTime<Utc>> {
         Ok(self
             .main
             .get::<_, Str, SerdeJson<DateTime<Utc>>>(rtxn, main_key::CREATED_AT_KEY)?
@@ -799,16 +1640,28 @@ impl Index {
     }
 }
 
+/// The reserved `_rankingScore` field is never displayed nor searchable,
+/// even when the user explicitly lists it in these settings.
+fn without_ranking_score(mut fields: Vec<&str>) -> Vec<&str> {
+    fields.retain(|name| *name != RANKING_SCORE_FIELD);
+    fields
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
+    use std::collections::BTreeMap;
     use std::ops::Deref;
 
+    use big_s::S;
     use heed::EnvOpenOptions;
-    use maplit::btreemap;
+    use maplit::{btreemap, hashset};
+    use roaring::RoaringBitmap;
+    use serde_json::json;
     use tempfile::TempDir;
 
-    use crate::update::{IndexDocuments, UpdateFormat};
-    use crate::Index;
+    use crate::facet::{FacetValue, FacetValueOrder};
+    use crate::update::{DeleteDocuments, IndexDocuments, Settings, UpdateFormat};
+    use crate::{obkv_to_json, FilterCondition, Index};
 
     pub(crate) struct TempIndex {
         inner: Index,
@@ -835,6 +1688,27 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn number_of_documents_and_is_empty() {
+        let index = TempIndex::new();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 0);
+        assert!(index.is_empty(&rtxn).unwrap());
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[{ "id": 1, "name": "kevin" }, { "id": 2, "name": "bob" }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 2);
+        assert!(!index.is_empty(&rtxn).unwrap());
+    }
+
     #[test]
     fn initial_field_distribution() {
         let path = tempfile::tempdir().unwrap();
@@ -908,4 +1782,902 @@ pub(crate) mod tests {
             }
         );
     }
+
+    #[test]
+    fn documents_where_number_field_gt() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { "updated_at".to_string() });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "updated_at": 1000 },
+            { "id": 2, "updated_at": 3000 },
+            { "id": 3, "updated_at": 2000 },
+            { "id": 4, "updated_at": [500, 2500] },
+            { "id": 5 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let ids: Vec<_> = index
+            .documents_where_number_field_gt(&rtxn, "updated_at", 2000.0)
+            .unwrap()
+            .map(|result| result.map(|(id, _)| id))
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(ids, vec![1, 3]);
+
+        let count = index.documents_where_number_field_gt(&rtxn, "unknown", 0.0).unwrap().count();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn content_fingerprint() {
+        let build = |content: &[u8]| {
+            let path = tempfile::tempdir().unwrap();
+            let mut options = EnvOpenOptions::new();
+            options.map_size(10 * 1024 * 1024); // 10 MB
+            let index = Index::new(options, &path).unwrap();
+
+            let mut wtxn = index.write_txn().unwrap();
+            let mut builder = Settings::new(&mut wtxn, &index, 0);
+            builder.set_filterable_fields(hashset! { "age".to_string() });
+            builder.execute(|_, _| ()).unwrap();
+
+            let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+            builder.update_format(UpdateFormat::Json);
+            builder.execute(content, |_, _| ()).unwrap();
+            wtxn.commit().unwrap();
+
+            let rtxn = index.read_txn().unwrap();
+            index.content_fingerprint(&rtxn).unwrap()
+        };
+
+        let first = build(
+            br#"[
+            { "id": 1, "name": "kevin", "age": 20 },
+            { "id": 2, "name": "kevina", "age": 21 },
+            { "id": 3, "name": "benoit", "age": 34 }
+        ]"#,
+        );
+        let second = build(
+            br#"[
+            { "id": 3, "age": 34, "name": "benoit" },
+            { "id": 1, "age": 20, "name": "kevin" },
+            { "id": 2, "age": 21, "name": "kevina" }
+        ]"#,
+        );
+        let third = build(
+            br#"[
+            { "id": 1, "name": "kevin", "age": 20 },
+            { "id": 2, "name": "kevina", "age": 22 },
+            { "id": 3, "name": "benoit", "age": 34 }
+        ]"#,
+        );
+
+        assert_eq!(first, second);
+        assert_ne!(first, third);
+    }
+
+    #[test]
+    fn array_facet_distribution() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { "label".to_string() });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "label": ["red", "blue"] },
+            { "id": 2, "label": ["blue", "green", 3] },
+            { "id": 3, "label": "red" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // Every element of an array is a facet value on its own.
+        let expected = btreemap! {
+            "3".to_string() => 1,
+            "blue".to_string() => 2,
+            "green".to_string() => 1,
+            "red".to_string() => 2,
+        };
+
+        let rtxn = index.read_txn().unwrap();
+        let distribution = index.global_facet_distribution(&rtxn, &["label"]).unwrap();
+        assert_eq!(distribution["label"], expected);
+
+        let candidates = index.search(&rtxn).execute().unwrap().candidates;
+        let distribution = index
+            .facets_distribution(&rtxn)
+            .facets(&["label"])
+            .candidates(candidates)
+            .execute()
+            .unwrap();
+        assert_eq!(distribution["label"], expected);
+    }
+
+    #[test]
+    fn documents_by_external_ids() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": "kevin", "age": 20 },
+            { "id": "tamo", "age": 27 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let documents =
+            index.documents_by_external_ids(&rtxn, &["tamo", "unknown", "kevin"]).unwrap();
+
+        let ids: Vec<_> = documents.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["tamo", "unknown", "kevin"]);
+        assert!(documents[1].1.is_none());
+
+        let fields: Vec<_> = fields_ids_map.ids().collect();
+        let tamo = obkv_to_json(&fields, &fields_ids_map, documents[0].1.unwrap()).unwrap();
+        assert_eq!(tamo["age"], json!(27));
+        let kevin = obkv_to_json(&fields, &fields_ids_map, documents[2].1.unwrap()).unwrap();
+        assert_eq!(kevin["age"], json!(20));
+    }
+
+    #[test]
+    fn facet_distribution_original_values() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { "city".to_string() });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "city": "New York" },
+            { "id": 2, "city": "New York" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let candidates = index.search(&rtxn).execute().unwrap().candidates;
+
+        let mut distribution = index.facets_distribution(&rtxn);
+        distribution.facets(&["city"]).with_original_values(true);
+        let original = btreemap! { "New York".to_string() => 2 };
+        assert_eq!(distribution.execute().unwrap()["city"], original);
+        distribution.candidates(candidates.clone());
+        assert_eq!(distribution.execute().unwrap()["city"], original);
+
+        let mut distribution = index.facets_distribution(&rtxn);
+        distribution.facets(&["city"]).with_original_values(false);
+        let normalized = btreemap! { "new york".to_string() => 2 };
+        assert_eq!(distribution.execute().unwrap()["city"], normalized);
+        distribution.candidates(candidates);
+        assert_eq!(distribution.execute().unwrap()["city"], normalized);
+    }
+
+    #[test]
+    fn global_facet_distribution() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { "age".to_string(), "name".to_string() });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "name": "kevin", "age": 20 },
+            { "id": 2, "name": "kevina", "age": 21 },
+            { "id": 3, "name": "benoit", "age": 20 },
+            { "id": 4, "name": "tamo", "age": 34 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let distribution = index.global_facet_distribution(&rtxn, &["age", "name"]).unwrap();
+
+        let candidates = index.search(&rtxn).execute().unwrap().candidates;
+        let expected = index
+            .facets_distribution(&rtxn)
+            .facets(&["age", "name"])
+            .candidates(candidates)
+            .execute()
+            .unwrap();
+
+        assert_eq!(distribution, expected);
+        assert_eq!(
+            distribution["age"],
+            btreemap! { "20".to_string() => 2, "21".to_string() => 1, "34".to_string() => 1 }
+        );
+    }
+
+    #[test]
+    fn grow_map_size() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(50 * 4096); // 200 KB
+        let index = Index::new(options, &path).unwrap();
+
+        let documents: Vec<_> = (0..2000)
+            .map(|i| json!({ "id": i, "name": format!("kevin{}", i), "age": i % 100 }))
+            .collect();
+        let content = serde_json::to_vec(&documents).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        let result = builder.execute(&content[..], |_, _| ());
+        assert!(matches!(
+            result,
+            Err(crate::Error::UserError(crate::error::UserError::MaxDatabaseSizeReached))
+        ));
+        drop(wtxn);
+
+        // An other copy of the index prevents it from being reopened.
+        let copy = index.clone();
+        let result = copy.grow_map_size(10 * 1024 * 1024);
+        assert!(matches!(
+            result,
+            Err(crate::Error::UserError(crate::error::UserError::IndexInUse))
+        ));
+
+        // The remaining copy is still usable but the index is closing, it can't be opened.
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 0);
+        drop(rtxn);
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        assert!(Index::new(options, &path).is_err());
+
+        let index = index.grow_map_size(10 * 1024 * 1024).unwrap(); // 10 MB
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(&content[..], |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 2000);
+    }
+
+    #[test]
+    fn compact_to() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let documents: Vec<_> = (0..2000)
+            .map(|i| json!({ "id": i, "name": format!("kevin{}", i), "age": i % 100 }))
+            .collect();
+        let content = serde_json::to_vec(&documents).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(&content[..], |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 1).unwrap();
+        for i in 100..2000 {
+            builder.delete_external_id(&i.to_string());
+        }
+        builder.execute().unwrap();
+        wtxn.commit().unwrap();
+
+        let compacted_path = tempfile::tempdir().unwrap();
+        index.compact_to(&compacted_path).unwrap();
+
+        let size = std::fs::metadata(path.path().join("data.mdb")).unwrap().len();
+        let compacted_size =
+            std::fs::metadata(compacted_path.path().join("data.mdb")).unwrap().len();
+        assert!(compacted_size < size);
+
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let compacted = Index::new(options, &compacted_path).unwrap();
+        let rtxn = compacted.read_txn().unwrap();
+        assert_eq!(compacted.number_of_documents(&rtxn).unwrap(), 100);
+        let docid = compacted.external_documents_ids(&rtxn).unwrap().get("42").unwrap();
+        let (_, obkv) = compacted.documents(&rtxn, Some(docid)).unwrap().pop().unwrap();
+        let fields_ids_map = compacted.fields_ids_map(&rtxn).unwrap();
+        let name = obkv.get(fields_ids_map.id("name").unwrap()).unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(name).unwrap(), json!("kevin42"));
+    }
+
+    #[test]
+    fn facet_distribution_stream() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { "color".to_string() });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "color": "red" },
+            { "id": 2, "color": ["blue", 12] },
+            { "id": 3, "color": "green" },
+            { "id": 4, "color": 3 },
+            { "id": 5, "color": ["blue", 3] }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let distribution: Vec<_> = index
+            .facet_distribution_stream(&rtxn, "color", None)
+            .unwrap()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        let expected = vec![
+            (FacetValue::from("blue"), 2),
+            (FacetValue::from("green"), 1),
+            (FacetValue::from("red"), 1),
+            (FacetValue::from(3.0), 2),
+            (FacetValue::from(12.0), 1),
+        ];
+        assert_eq!(distribution, expected);
+
+        // Only the values of the candidates are yielded.
+        let candidates: RoaringBitmap = (0..3).collect();
+        let distribution: Vec<_> = index
+            .facet_distribution_stream(&rtxn, "color", Some(&candidates))
+            .unwrap()
+            .take(2)
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            distribution,
+            vec![(FacetValue::from("blue"), 1), (FacetValue::from("green"), 1)]
+        );
+
+        let count = index.facet_distribution_stream(&rtxn, "unknown", None).unwrap().count();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn facet_values_by_value_and_by_count() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("color") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "color": "red" },
+            { "id": 2, "color": ["blue", 12] },
+            { "id": 3, "color": ["green", "red"] },
+            { "id": 4, "color": 3 },
+            { "id": 5, "color": ["red", 3] }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let values: Vec<_> = index
+            .facet_values(&rtxn, "color", FacetValueOrder::ByValue)
+            .unwrap()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        let expected = vec![
+            (FacetValue::from("blue"), 1),
+            (FacetValue::from("green"), 1),
+            (FacetValue::from("red"), 3),
+            (FacetValue::from(3.0), 2),
+            (FacetValue::from(12.0), 1),
+        ];
+        assert_eq!(values, expected);
+
+        let values: Vec<_> = index
+            .facet_values(&rtxn, "color", FacetValueOrder::ByCount)
+            .unwrap()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        let expected = vec![
+            (FacetValue::from("red"), 3),
+            (FacetValue::from(3.0), 2),
+            (FacetValue::from("blue"), 1),
+            (FacetValue::from("green"), 1),
+            (FacetValue::from(12.0), 1),
+        ];
+        assert_eq!(values, expected);
+
+        let count = index.facet_values(&rtxn, "unknown", FacetValueOrder::ByCount).unwrap().count();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn facet_values_search() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("color") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "color": "Red" },
+            { "id": 2, "color": ["reddish", 12] },
+            { "id": 3, "color": ["green", "Red"] },
+            { "id": 4, "color": "blue" },
+            { "id": 5, "color": ["rose", "reddish"] },
+            { "id": 6, "color": "reddish" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let values = index.facet_values_search(&rtxn, "color", "RED", 10, None).unwrap();
+        assert_eq!(values, vec![(S("reddish"), 3), (S("Red"), 2)]);
+
+        let values = index.facet_values_search(&rtxn, "color", "r", 2, None).unwrap();
+        assert_eq!(values, vec![(S("reddish"), 3), (S("Red"), 2)]);
+
+        // The counts only consider the candidates.
+        let candidates = index.external_documents_ids(&rtxn).unwrap();
+        let candidates: RoaringBitmap =
+            ["1", "3", "6"].iter().map(|id| candidates.get(id).unwrap()).collect();
+        let values =
+            index.facet_values_search(&rtxn, "color", "re", 10, Some(&candidates)).unwrap();
+        assert_eq!(values, vec![(S("Red"), 2), (S("reddish"), 1)]);
+
+        let values = index.facet_values_search(&rtxn, "color", "yellow", 10, None).unwrap();
+        assert!(values.is_empty());
+        let values = index.facet_values_search(&rtxn, "unknown", "r", 10, None).unwrap();
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn facet_distributions() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("color"), S("size"), S("brand") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "color": "Red", "size": 40, "brand": "nike" },
+            { "id": 2, "color": ["blue", 12], "size": 42, "brand": "adidas" },
+            { "id": 3, "color": "green", "size": [40, 41] },
+            { "id": 4, "color": 3, "brand": "Nike" },
+            { "id": 5, "color": ["blue", 3], "size": 42, "brand": "puma" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let names = ["color", "size", "brand"];
+        let fields: Vec<_> = names.iter().map(|name| fields_ids_map.id(name).unwrap()).collect();
+
+        let all: RoaringBitmap = (0..5).collect();
+        let some: RoaringBitmap = vec![0, 2, 3].into_iter().collect();
+        for candidates in vec![None, Some(all), Some(some)] {
+            let distributions =
+                index.facet_distributions(&rtxn, &fields, candidates.as_ref()).unwrap();
+            assert_eq!(distributions.len(), 3);
+
+            for (name, field_id) in names.iter().zip(&fields) {
+                let mut facet_distribution = index.facets_distribution(&rtxn);
+                facet_distribution.facets(Some(name));
+                if let Some(candidates) = candidates.clone() {
+                    facet_distribution.candidates(candidates);
+                }
+                let expected = facet_distribution.execute().unwrap().remove(*name).unwrap();
+                let distribution: BTreeMap<_, _> =
+                    distributions[field_id].clone().into_iter().collect();
+                assert_eq!(distribution, expected, "{} with {:?}", name, candidates);
+            }
+        }
+    }
+
+    #[test]
+    fn external_ids_stream() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": "kevin", "age": 20 },
+            { "id": "benoit", "age": 34 },
+            { "id": "tamo", "age": 21 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 1).unwrap();
+        builder.delete_external_id("tamo");
+        builder.execute().unwrap();
+
+        let content = &br#"[{ "id": "alice", "age": 28 }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 2);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let external_ids: Vec<_> =
+            index.external_ids_stream(&rtxn).unwrap().map(Result::unwrap).collect();
+        let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+        let expected: Vec<_> = vec!["alice", "benoit", "kevin"]
+            .into_iter()
+            .map(|id| (id.to_string(), external_documents_ids.get(id).unwrap()))
+            .collect();
+        assert_eq!(external_ids, expected);
+    }
+
+    #[test]
+    fn primary_key_field_id() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.primary_key(&rtxn).unwrap(), None);
+        assert_eq!(index.primary_key_field_id(&rtxn).unwrap(), None);
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[{ "name": "kevin", "docid": 1 }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        assert_eq!(index.primary_key(&rtxn).unwrap(), Some("docid"));
+        assert!(fields_ids_map.id("docid").is_some());
+        assert_eq!(index.primary_key_field_id(&rtxn).unwrap(), fields_ids_map.id("docid"));
+    }
+
+    #[test]
+    fn documents_json() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": 1, "name": "kevin", "age": 20 },
+            { "id": 2, "name": "bob", "age": 23 },
+            { "id": 3, "name": "kevina" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_displayed_fields(vec!["id".to_string(), "name".to_string()]);
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let ids = vec![2, 0];
+
+        // The same documents retrieved the manual way.
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let displayed_fields = index.displayed_fields_ids(&rtxn).unwrap().unwrap();
+        let expected: Vec<_> = index
+            .documents(&rtxn, ids.clone())
+            .unwrap()
+            .into_iter()
+            .map(|(_, obkv)| obkv_to_json(&displayed_fields, &fields_ids_map, obkv).unwrap())
+            .map(serde_json::Value::Object)
+            .collect();
+
+        let documents = index.documents_json(&rtxn, ids.clone(), None).unwrap();
+        assert_eq!(documents, expected);
+        assert_eq!(
+            documents,
+            vec![json!({ "id": 3, "name": "kevina" }), json!({ "id": 1, "name": "kevin" })]
+        );
+
+        // A projection can only restrict the displayed fields.
+        let id = fields_ids_map.id("id").unwrap();
+        let age = fields_ids_map.id("age").unwrap();
+        let documents = index.documents_json(&rtxn, ids, Some(&hashset! { id, age })).unwrap();
+        assert_eq!(documents, vec![json!({ "id": 3 }), json!({ "id": 1 })]);
+
+        // Asking for an unknown document returns an error.
+        assert!(index.documents_json(&rtxn, vec![42], None).is_err());
+    }
+
+    #[test]
+    fn remove_field() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { "age".to_string(), "name".to_string() });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "name": "kevin", "age": 20 },
+            { "id": 2, "name": "bob", "age": 23 },
+            { "id": 3, "name": "kevina" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let age = index.fields_ids_map(&rtxn).unwrap().id("age").unwrap();
+        assert_eq!(index.number_faceted_documents_ids(&rtxn, age).unwrap().len(), 2);
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        assert!(index.remove_field(&mut wtxn, "age").unwrap());
+        // The field is now unknown.
+        assert!(!index.remove_field(&mut wtxn, "age").unwrap());
+        // The primary key cannot be removed.
+        assert!(index.remove_field(&mut wtxn, "id").is_err());
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let documents = index.documents_json(&rtxn, vec![0, 1, 2], None).unwrap();
+        assert_eq!(
+            documents,
+            vec![
+                json!({ "id": 1, "name": "kevin" }),
+                json!({ "id": 2, "name": "bob" }),
+                json!({ "id": 3, "name": "kevina" }),
+            ]
+        );
+
+        assert!(index.fields_ids_map(&rtxn).unwrap().id("age").is_none());
+        assert!(!index.field_distribution(&rtxn).unwrap().contains_key("age"));
+        assert!(!index.faceted_fields_ids(&rtxn).unwrap().contains(&age));
+        assert!(index.number_faceted_documents_ids(&rtxn, age).unwrap().is_empty());
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "age > 10").unwrap();
+        assert!(condition.evaluate(&rtxn, &index).unwrap().is_empty());
+
+        // The other fields are still filterable.
+        let condition = FilterCondition::from_str(&rtxn, &index, "name = bob").unwrap();
+        assert_eq!(condition.evaluate(&rtxn, &index).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rename_field() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("desc") });
+        builder.set_searchable_fields(vec![S("name"), S("desc")]);
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "name": "kevin", "desc": "blue" },
+            { "id": 2, "name": "bob", "desc": "red" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let desc = index.fields_ids_map(&rtxn).unwrap().id("desc").unwrap();
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        // The new name must not be used by another field.
+        assert!(index.rename_field(&mut wtxn, "desc", "name").is_err());
+        assert!(!index.rename_field(&mut wtxn, "unknown", "description").unwrap());
+        assert!(index.rename_field(&mut wtxn, "desc", "description").unwrap());
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        assert_eq!(fields_ids_map.id("description"), Some(desc));
+        assert_eq!(fields_ids_map.id("desc"), None);
+        assert_eq!(index.filterable_fields(&rtxn).unwrap(), hashset! { S("description") });
+        assert_eq!(index.searchable_fields(&rtxn).unwrap(), Some(vec!["name", "description"]));
+        let field_distribution = index.field_distribution(&rtxn).unwrap();
+        assert_eq!(field_distribution.get("description"), Some(&2));
+        assert!(!field_distribution.contains_key("desc"));
+
+        let documents = index.documents_json(&rtxn, vec![0], None).unwrap();
+        assert_eq!(documents, vec![json!({ "id": 1, "name": "kevin", "description": "blue" })]);
+
+        // The facets and the words are still there, under the new name.
+        let condition = FilterCondition::from_str(&rtxn, &index, "description = red").unwrap();
+        assert_eq!(condition.evaluate(&rtxn, &index).unwrap().len(), 1);
+        assert!(FilterCondition::from_str(&rtxn, &index, "desc = red").is_err());
+
+        let result = index.search(&rtxn).query("blue").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![0]);
+    }
+
+    #[test]
+    fn rename_field_with_nested_facets() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("variants.color"), S("variantsize") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "variants": [{ "color": "blue" }, { "color": "red" }], "variantsize": 3 },
+            { "id": 2, "variants": { "color": "green" }, "variantsize": 4 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let nested = index.fields_ids_map(&rtxn).unwrap().id("variants.color").unwrap();
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        assert!(index.rename_field(&mut wtxn, "variants", "options").unwrap());
+        wtxn.commit().unwrap();
+
+        // The nested faceted field follows its parent, the fields sharing a prefix do not.
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        assert_eq!(fields_ids_map.id("options.color"), Some(nested));
+        assert_eq!(fields_ids_map.id("variants.color"), None);
+        assert!(fields_ids_map.id("variantsize").is_some());
+        assert_eq!(
+            index.filterable_fields(&rtxn).unwrap(),
+            hashset! { S("options.color"), S("variantsize") }
+        );
+
+        let field_distribution = index.field_distribution(&rtxn).unwrap();
+        assert!(field_distribution.keys().all(|name| !name.starts_with("variants.")));
+        assert_eq!(field_distribution.get("options"), Some(&2));
+        assert_eq!(field_distribution.get("variantsize"), Some(&2));
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "options.color = red").unwrap();
+        assert_eq!(condition.evaluate(&rtxn, &index).unwrap().iter().collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn rename_field_with_colliding_nested_field() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("variants.color") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "variants": { "color": "blue" } },
+            { "id": 2, "options.color": "green" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // The "options" field is unknown but "variants.color" would become "options.color".
+        let mut wtxn = index.write_txn().unwrap();
+        let result = index.rename_field(&mut wtxn, "variants", "options");
+        assert!(matches!(
+            result,
+            Err(crate::Error::UserError(crate::error::UserError::FieldAlreadyExists {
+                ref field_name
+            }))
+                if field_name == "options.color"
+        ));
+        drop(wtxn);
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        assert!(fields_ids_map.id("variants").is_some());
+        assert!(fields_ids_map.id("variants.color").is_some());
+        assert!(fields_ids_map.id("options").is_none());
+    }
+
+    #[test]
+    fn rename_date_field() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("created") });
+        builder.set_date_fields(hashset! { S("created") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "created": "2020-12-31" },
+            { "id": 2, "created": "2021-03-15" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        assert!(index.rename_field(&mut wtxn, "created", "published").unwrap());
+        wtxn.commit().unwrap();
+
+        // The renamed field is still a date field, the dates are accepted in its filters.
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.date_fields(&rtxn).unwrap(), hashset! { S("published") });
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "published >= 2021-01-01").unwrap();
+        assert_eq!(condition.evaluate(&rtxn, &index).unwrap().iter().collect::<Vec<_>>(), vec![1]);
+    }
 }
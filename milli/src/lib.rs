@@ -8,6 +8,7 @@ pub mod facet;
 mod fields_ids_map;
 pub mod heed_codec;
 pub mod index;
+mod primary_key_policy;
 pub mod proximity;
 mod search;
 pub mod tree_level;
@@ -21,11 +22,11 @@ use fxhash::{FxHasher32, FxHasher64};
 pub use grenad::CompressionType;
 use serde_json::{Map, Value};
 
-pub use self::criterion::{default_criteria, AscDesc, Criterion};
+pub use self::criterion::{default_criteria, AscDesc, Criterion, RANKING_SCORE_FIELD};
 pub use self::error::{
     Error, FieldIdMapMissingEntry, InternalError, SerializationError, UserError,
 };
-pub use self::external_documents_ids::ExternalDocumentsIds;
+pub use self::external_documents_ids::{ExternalDocumentsIds, ExternalDocumentsIdsStream};
 pub use self::fields_ids_map::FieldsIdsMap;
 pub use self::heed_codec::{
     BEU32StrCodec, BoRoaringBitmapCodec, BoRoaringBitmapLenCodec, CboRoaringBitmapCodec,
@@ -33,7 +34,11 @@ pub use self::heed_codec::{
     RoaringBitmapLenCodec, StrLevelPositionCodec, StrStrU8Codec,
 };
 pub use self::index::Index;
-pub use self::search::{FacetDistribution, FilterCondition, MatchingWords, Search, SearchResult};
+pub use self::primary_key_policy::PrimaryKeyPolicy;
+pub use self::search::{
+    FacetDistribution, FilterCondition, MatchingWords, OwnedSearchResult, QueryTerm, Search,
+    SearchResult,
+};
 pub use self::tree_level::TreeLevel;
 
 pub type Result<T> = std::result::Result<T, error::Error>;
@@ -53,24 +58,46 @@ pub type Position = u32;
 pub type FieldDistribution = BTreeMap<String, u64>;
 
 /// Transform a raw obkv store into a JSON Object.
+///
+/// The fields ids that are not known by the `fields_ids_map` are skipped and logged,
+/// use [`obkv_to_json_with_unknown_fields`] to retrieve them.
 pub fn obkv_to_json(
     displayed_fields: &[FieldId],
     fields_ids_map: &FieldsIdsMap,
     obkv: obkv::KvReaderU16,
 ) -> Result<Map<String, Value>> {
-    displayed_fields
-        .iter()
-        .copied()
-        .flat_map(|id| obkv.get(id).map(|value| (id, value)))
-        .map(|(id, value)| {
-            let name = fields_ids_map.name(id).ok_or(error::FieldIdMapMissingEntry::FieldId {
-                field_id: id,
-                process: "obkv_to_json",
-            })?;
-            let value = serde_json::from_slice(value).map_err(error::InternalError::SerdeJson)?;
-            Ok((name.to_owned(), value))
-        })
-        .collect()
+    let (map, unknown_fields_ids) =
+        obkv_to_json_with_unknown_fields(displayed_fields, fields_ids_map, obkv)?;
+    if !unknown_fields_ids.is_empty() {
+        log::warn!("obkv_to_json: skipped unknown fields ids {:?}", unknown_fields_ids);
+    }
+    Ok(map)
+}
+
+/// Transform a raw obkv store into a JSON Object and returns the fields ids that
+/// are not known by the `fields_ids_map`, these fields are skipped.
+pub fn obkv_to_json_with_unknown_fields(
+    displayed_fields: &[FieldId],
+    fields_ids_map: &FieldsIdsMap,
+    obkv: obkv::KvReaderU16,
+) -> Result<(Map<String, Value>, Vec<FieldId>)> {
+    let mut map = Map::new();
+    let mut unknown_fields_ids = Vec::new();
+
+    for id in displayed_fields.iter().copied() {
+        if let Some(value) = obkv.get(id) {
+            match fields_ids_map.name(id) {
+                Some(name) => {
+                    let value =
+                        serde_json::from_slice(value).map_err(error::InternalError::SerdeJson)?;
+                    map.insert(name.to_owned(), value);
+                }
+                None => unknown_fields_ids.push(id),
+            }
+        }
+    }
+
+    Ok((map, unknown_fields_ids))
 }
 
 /// Transform a JSON value into a string that can be indexed.
@@ -174,4 +201,29 @@ mod tests {
         // the distance of hard separators is clamped to 8 anyway.
         assert_eq!(string, "name: John Doe. . 43. hello. I. am. fine. . ");
     }
+
+    #[test]
+    fn obkv_to_json_unknown_field_id() {
+        let mut fields_ids_map = FieldsIdsMap::new();
+        let id = fields_ids_map.insert("id").unwrap();
+        let name = fields_ids_map.insert("name").unwrap();
+        let unknown = 42;
+
+        let mut buffer = Vec::new();
+        let mut writer = obkv::KvWriter::<_, FieldId>::new(&mut buffer);
+        writer.insert(id, br#"1"#).unwrap();
+        writer.insert(name, br#""kevin""#).unwrap();
+        writer.insert(unknown, br#""corrupted""#).unwrap();
+        writer.finish().unwrap();
+
+        let obkv = obkv::KvReaderU16::new(&buffer);
+        let (map, unknown_fields_ids) =
+            obkv_to_json_with_unknown_fields(&[id, name, unknown], &fields_ids_map, obkv).unwrap();
+        assert_eq!(Value::Object(map), json!({ "id": 1, "name": "kevin" }));
+        assert_eq!(unknown_fields_ids, vec![unknown]);
+
+        let obkv = obkv::KvReaderU16::new(&buffer);
+        let map = obkv_to_json(&[id, name, unknown], &fields_ids_map, obkv).unwrap();
+        assert_eq!(Value::Object(map), json!({ "id": 1, "name": "kevin" }));
+    }
 }
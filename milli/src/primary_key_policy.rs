@@ -0,0 +1,87 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How the primary key values of the documents are converted into external document ids
+/// when the documents are added.
+///
+/// The strings and the numbers are always accepted, the other values are rejected by default.
+/// The deletion never rejects the ids of the already indexed documents, see
+/// [`PrimaryKeyPolicy::indexed_document_id`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrimaryKeyPolicy {
+    /// Accepts the booleans, converted into the `true` and `false` document ids.
+    pub allow_booleans: bool,
+    /// Rejects the numbers with a fractional part, e.g. `1.5`, only the integers are accepted.
+    pub reject_fractional_numbers: bool,
+}
+
+impl PrimaryKeyPolicy {
+    /// Returns the external document id of this primary key value, if accepted.
+    ///
+    /// The ids of the strings must still be validated, they can contain invalid characters,
+    /// the ids of the numbers are their canonical representation and are always valid.
+    pub fn document_id<'a>(&self, value: &'a Value) -> Option<Cow<'a, str>> {
+        match value {
+            Value::String(string) => Some(Cow::Borrowed(string.as_str())),
+            Value::Number(number) if number.is_u64() || number.is_i64() => {
+                Some(Cow::Owned(number.to_string()))
+            }
+            Value::Number(number) if !self.reject_fractional_numbers => {
+                Some(Cow::Owned(number.to_string()))
+            }
+            Value::Bool(boolean) if self.allow_booleans => Some(Cow::Owned(boolean.to_string())),
+            _otherwise => None,
+        }
+    }
+
+    /// Returns the external document id of the primary key value of an indexed document.
+    ///
+    /// Any value a policy could have accepted is converted, the policy may have been
+    /// made stricter since the document was added and it must still be deletable.
+    pub fn indexed_document_id(value: &Value) -> Option<Cow<str>> {
+        let policy = PrimaryKeyPolicy { allow_booleans: true, reject_fractional_numbers: false };
+        policy.document_id(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn document_ids() {
+        let policy = PrimaryKeyPolicy::default();
+        assert_eq!(policy.document_id(&json!("hello")).as_deref(), Some("hello"));
+        assert_eq!(policy.document_id(&json!(42)).as_deref(), Some("42"));
+        assert_eq!(policy.document_id(&json!(-42)).as_deref(), Some("-42"));
+        assert_eq!(policy.document_id(&json!(4.2)).as_deref(), Some("4.2"));
+        assert_eq!(policy.document_id(&json!(true)), None);
+        assert_eq!(policy.document_id(&json!(null)), None);
+
+        let policy = PrimaryKeyPolicy { allow_booleans: true, ..PrimaryKeyPolicy::default() };
+        assert_eq!(policy.document_id(&json!(true)).as_deref(), Some("true"));
+
+        let policy =
+            PrimaryKeyPolicy { reject_fractional_numbers: true, ..PrimaryKeyPolicy::default() };
+        assert_eq!(policy.document_id(&json!(42)).as_deref(), Some("42"));
+        assert_eq!(policy.document_id(&json!(4.2)), None);
+
+        // The indexed documents ids are never rejected.
+        assert_eq!(PrimaryKeyPolicy::indexed_document_id(&json!(4.2)).as_deref(), Some("4.2"));
+        assert_eq!(PrimaryKeyPolicy::indexed_document_id(&json!(true)).as_deref(), Some("true"));
+    }
+
+    #[test]
+    fn deserialize_without_the_new_fields() {
+        let policy: PrimaryKeyPolicy = serde_json::from_str(r#"{"allow_booleans":true}"#).unwrap();
+        assert_eq!(
+            policy,
+            PrimaryKeyPolicy { allow_booleans: true, ..PrimaryKeyPolicy::default() }
+        );
+    }
+}
@@ -4,6 +4,8 @@ use crate::{Attribute, Position};
 
 pub const ONE_ATTRIBUTE: u32 = 1000;
 pub const MAX_DISTANCE: u32 = 8;
+/// The maximum proximity between two words stored in the word pair proximity database.
+pub const DEFAULT_MAX_PROXIMITY_DISTANCE: u8 = MAX_DISTANCE as u8 - 1;
 
 pub fn index_proximity(lhs: u32, rhs: u32) -> u32 {
     if lhs <= rhs {
@@ -10,6 +10,7 @@ use crate::search::criteria::{
     resolve_query_tree, Context, Criterion, CriterionParameters, CriterionResult,
 };
 use crate::search::query_tree::{Operation, PrimitiveQueryPart};
+use crate::search::{word_derivations, WordDerivationsCache};
 use crate::{Result, TreeLevel};
 
 pub struct Exactness<'t> {
@@ -19,6 +20,7 @@ pub struct Exactness<'t> {
     bucket_candidates: RoaringBitmap,
     parent: Box<dyn Criterion + 't>,
     query: Vec<ExactQueryPart>,
+    prefix_tier: bool,
 }
 
 impl<'t> Exactness<'t> {
@@ -26,6 +28,7 @@ impl<'t> Exactness<'t> {
         ctx: &'t dyn Context<'t>,
         parent: Box<dyn Criterion + 't>,
         primitive_query: &[PrimitiveQueryPart],
+        prefix_tier: bool,
     ) -> heed::Result<Self> {
         let mut query: Vec<_> = Vec::with_capacity(primitive_query.len());
         for part in primitive_query {
@@ -39,6 +42,7 @@ impl<'t> Exactness<'t> {
             bucket_candidates: RoaringBitmap::new(),
             parent,
             query,
+            prefix_tier,
         })
     }
 }
@@ -61,7 +65,13 @@ impl<'t> Criterion for Exactness<'t> {
                     self.query_tree = None;
                 }
                 Some(state) => {
-                    let (candidates, state) = resolve_state(self.ctx, take(state), &self.query)?;
+                    let (candidates, state) = resolve_state(
+                        self.ctx,
+                        take(state),
+                        &self.query,
+                        self.prefix_tier,
+                        params.wdcache,
+                    )?;
                     self.state = state;
 
                     return Ok(Some(CriterionResult {
@@ -124,7 +134,8 @@ enum State {
     ExactAttribute(RoaringBitmap),
     /// Extract the documents that have an attribute that starts with exactly the query.
     AttributeStartsWith(RoaringBitmap),
-    /// Rank the remaining documents by the number of exact words contained.
+    /// Rank the remaining documents by the number of exact words contained, the documents
+    /// that contain all the words either exactly or as prefixes come next when asked.
     ExactWords(RoaringBitmap),
     Remainings(Vec<RoaringBitmap>),
 }
@@ -169,6 +180,8 @@ fn resolve_state(
     ctx: &dyn Context,
     state: State,
     query: &[ExactQueryPart],
+    prefix_tier: bool,
+    wdcache: &mut WordDerivationsCache,
 ) -> Result<(RoaringBitmap, Option<State>)> {
     use State::*;
     match state {
@@ -252,6 +265,27 @@ fn resolve_state(
             all_exact_candidates &= &allowed_candidates;
             allowed_candidates -= &all_exact_candidates;
 
+            // compute documents that contain all words, exactly or as prefixes,
+            // they are ranked before the documents that contain typos.
+            if prefix_tier {
+                let mut parts_prefix_candidates_array = Vec::with_capacity(number_of_part);
+                for (part, exact_candidates) in query.iter().zip(&parts_candidates_array) {
+                    let mut candidates = exact_candidates.clone();
+                    if let ExactQueryPart::Synonyms(synonyms) = part {
+                        for synonym in synonyms {
+                            candidates |= prefix_docids(ctx, synonym, wdcache)?;
+                        }
+                    }
+                    parts_prefix_candidates_array.push(candidates);
+                }
+
+                let mut all_prefix_candidates =
+                    intersection_of(parts_prefix_candidates_array.iter().collect());
+                all_prefix_candidates &= &allowed_candidates;
+                allowed_candidates -= &all_prefix_candidates;
+                candidates_array.push(all_prefix_candidates);
+            }
+
             // push the result of combinations of exact words grouped by the number of exact words contained by documents.
             for c_count in (1..number_of_part).rev() {
                 let mut combinations_candidates = parts_candidates_array
@@ -288,6 +322,23 @@ fn resolve_state(
     }
 }
 
+/// Returns the documents that contain a word starting with the given one.
+fn prefix_docids(
+    ctx: &dyn Context,
+    word: &str,
+    wdcache: &mut WordDerivationsCache,
+) -> Result<RoaringBitmap> {
+    if ctx.in_prefix_cache(word) {
+        return Ok(ctx.word_prefix_docids(word)?.unwrap_or_default());
+    }
+
+    let mut docids = RoaringBitmap::new();
+    for (word, _typo) in word_derivations(word, true, 0, ctx.words_fst(), wdcache)? {
+        docids |= ctx.word_docids(word)?.unwrap_or_default();
+    }
+    Ok(docids)
+}
+
 fn attribute_start_with_docids(
     ctx: &dyn Context,
     attribute_id: u32,
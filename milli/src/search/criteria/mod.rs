@@ -10,9 +10,10 @@ use self::initial::Initial;
 use self::proximity::Proximity;
 use self::r#final::Final;
 use self::typo::Typo;
+use self::word_count::WordCount;
 use self::words::Words;
 use super::query_tree::{Operation, PrimitiveQueryPart, Query, QueryKind};
-use crate::criterion::AscDesc as AscDescName;
+use crate::criterion::{AscDesc as AscDescName, RANKING_SCORE_FIELD};
 use crate::search::{word_derivations, WordDerivationsCache};
 use crate::{DocumentId, FieldId, Index, Result, TreeLevel};
 
@@ -23,6 +24,7 @@ pub mod r#final;
 mod initial;
 mod proximity;
 mod typo;
+mod word_count;
 mod words;
 
 pub trait Criterion {
@@ -237,7 +239,11 @@ impl<'c> Context<'c> for CriteriaBuilder<'c> {
     fn searchable_fields_ids(&self) -> Result<Vec<FieldId>> {
         match self.index.searchable_fields_ids(self.rtxn)? {
             Some(searchable_fields_ids) => Ok(searchable_fields_ids),
-            None => Ok(self.index.fields_ids_map(self.rtxn)?.ids().collect()),
+            None => {
+                let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+                let ranking_score_id = fields_ids_map.id(RANKING_SCORE_FIELD);
+                Ok(fields_ids_map.ids().filter(|id| Some(*id) != ranking_score_id).collect())
+            }
         }
     }
 
@@ -310,7 +316,17 @@ impl<'t> CriteriaBuilder<'t> {
                 },
                 Name::Proximity => Box::new(Proximity::new(self, criterion)),
                 Name::Attribute => Box::new(Attribute::new(self, criterion)),
-                Name::Exactness => Box::new(Exactness::new(self, criterion, &primitive_query)?),
+                Name::Exactness => {
+                    let prefix_tier = self.index.exactness_prefix_tier(&self.rtxn)?;
+                    Box::new(Exactness::new(self, criterion, &primitive_query, prefix_tier)?)
+                }
+                Name::WordCount => Box::new(WordCount::new(self, criterion)),
+                Name::RankingScore => Box::new(AscDesc::desc(
+                    &self.index,
+                    &self.rtxn,
+                    criterion,
+                    RANKING_SCORE_FIELD.to_string(),
+                )?),
                 Name::Asc(field) => {
                     Box::new(AscDesc::asc(&self.index, &self.rtxn, criterion, field)?)
                 }
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::mem::take;
+
+use log::debug;
+use roaring::RoaringBitmap;
+
+use super::{resolve_query_tree, Context, Criterion, CriterionParameters, CriterionResult};
+use crate::proximity::ONE_ATTRIBUTE;
+use crate::search::query_tree::{Operation, Query, QueryKind};
+use crate::search::{word_derivations, WordDerivationsCache};
+use crate::{FieldId, Result, TreeLevel};
+
+/// The biggest number of words of an attribute that is stored in
+/// the `field_id_word_count_docids` database, longer attributes are not counted.
+const MAX_COUNTED_WORD_COUNT: u8 = 10;
+
+/// The biggest position of a word in a counted attribute, two consecutive
+/// words are at most 8 positions apart when separated by a hard separator.
+const MAX_COUNTED_POSITION: u32 = (MAX_COUNTED_WORD_COUNT as u32 - 1) * 8;
+
+pub struct WordCount<'t> {
+    ctx: &'t dyn Context<'t>,
+    query_tree: Option<Operation>,
+    candidates: Option<RoaringBitmap>,
+    /// The candidates in which the query matched, for each field short enough to be counted.
+    matched_fields: HashMap<FieldId, RoaringBitmap>,
+    word_count: u8,
+    bucket_candidates: RoaringBitmap,
+    parent: Box<dyn Criterion + 't>,
+}
+
+impl<'t> WordCount<'t> {
+    pub fn new(ctx: &'t dyn Context<'t>, parent: Box<dyn Criterion + 't>) -> Self {
+        WordCount {
+            ctx,
+            query_tree: None,
+            candidates: None,
+            matched_fields: HashMap::new(),
+            word_count: 1,
+            bucket_candidates: RoaringBitmap::new(),
+            parent,
+        }
+    }
+}
+
+impl<'t> Criterion for WordCount<'t> {
+    #[logging_timer::time("WordCount::{}")]
+    fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
+        // remove excluded candidates when next is called, instead of doing it in the loop.
+        if let Some(candidates) = self.candidates.as_mut() {
+            *candidates -= params.excluded_candidates;
+        }
+
+        loop {
+            debug!("WordCount at word count {} ({:?})", self.word_count, self.candidates);
+
+            match self.candidates.as_mut() {
+                Some(candidates) if candidates.is_empty() => {
+                    // reset state
+                    self.candidates = None;
+                    self.query_tree = None;
+                    self.matched_fields.clear();
+                }
+                Some(candidates) => {
+                    let bucket = if self.word_count > MAX_COUNTED_WORD_COUNT {
+                        // The remaining documents only have long attributes.
+                        take(candidates)
+                    } else {
+                        // Only the word count of the fields the query matched
+                        // in a document are considered for this document.
+                        let mut bucket = RoaringBitmap::new();
+                        for (&field_id, matched) in &self.matched_fields {
+                            if let Some(docids) =
+                                self.ctx.field_id_word_count_docids(field_id, self.word_count)?
+                            {
+                                bucket |= docids & matched;
+                            }
+                        }
+                        bucket &= &*candidates;
+                        *candidates -= &bucket;
+                        self.word_count += 1;
+                        bucket
+                    };
+
+                    if bucket.is_empty() {
+                        continue;
+                    }
+
+                    return Ok(Some(CriterionResult {
+                        query_tree: self.query_tree.clone(),
+                        candidates: Some(bucket),
+                        filtered_candidates: None,
+                        bucket_candidates: Some(take(&mut self.bucket_candidates)),
+                    }));
+                }
+                None => match self.parent.next(params)? {
+                    Some(CriterionResult {
+                        query_tree: Some(query_tree),
+                        candidates,
+                        filtered_candidates,
+                        bucket_candidates,
+                    }) => {
+                        let mut candidates = match candidates {
+                            Some(candidates) => candidates,
+                            None => {
+                                resolve_query_tree(self.ctx, &query_tree, params.wdcache)?
+                                    - params.excluded_candidates
+                            }
+                        };
+
+                        if let Some(filtered_candidates) = filtered_candidates {
+                            candidates &= filtered_candidates;
+                        }
+
+                        match bucket_candidates {
+                            Some(bucket_candidates) => self.bucket_candidates |= bucket_candidates,
+                            None => self.bucket_candidates |= &candidates,
+                        }
+
+                        self.matched_fields = matched_fields_candidates(
+                            self.ctx,
+                            &query_tree,
+                            &candidates,
+                            params.wdcache,
+                        )?;
+                        self.candidates = Some(candidates);
+                        self.query_tree = Some(query_tree);
+                        self.word_count = 1;
+                    }
+                    Some(CriterionResult {
+                        query_tree: None,
+                        candidates,
+                        filtered_candidates,
+                        bucket_candidates,
+                    }) => {
+                        return Ok(Some(CriterionResult {
+                            query_tree: None,
+                            candidates,
+                            filtered_candidates,
+                            bucket_candidates,
+                        }));
+                    }
+                    None => return Ok(None),
+                },
+            }
+        }
+    }
+}
+
+/// Returns the candidates in which any of the words of the query tree appears, for each field.
+///
+/// Only the fields short enough to be counted are considered, their words are all in the first
+/// positions of the field, the candidates are therefore retrieved from the word level positions
+/// postings of these positions only.
+fn matched_fields_candidates(
+    ctx: &dyn Context,
+    query_tree: &Operation,
+    candidates: &RoaringBitmap,
+    wdcache: &mut WordDerivationsCache,
+) -> Result<HashMap<FieldId, RoaringBitmap>> {
+    fn query_tree_words(
+        ctx: &dyn Context,
+        operation: &Operation,
+        wdcache: &mut WordDerivationsCache,
+        output: &mut Vec<(String, bool)>,
+    ) -> Result<()> {
+        match operation {
+            Operation::And(ops) | Operation::Or(_, ops) => {
+                for op in ops {
+                    query_tree_words(ctx, op, wdcache, output)?;
+                }
+            }
+            Operation::Phrase(words) => {
+                output.extend(words.iter().map(|word| (word.clone(), false)));
+            }
+            Operation::Query(Query { prefix, kind }) => match kind {
+                QueryKind::Exact { word, .. } if !*prefix || ctx.in_prefix_cache(word) => {
+                    output.push((word.clone(), *prefix));
+                }
+                QueryKind::Exact { word, .. } => {
+                    let words = word_derivations(word, true, 0, ctx.words_fst(), wdcache)?;
+                    output.extend(words.iter().map(|(word, _)| (word.clone(), false)));
+                }
+                QueryKind::Tolerant { typo, word } => {
+                    let words = word_derivations(word, *prefix, *typo, ctx.words_fst(), wdcache)?;
+                    output.extend(words.iter().map(|(word, _)| (word.clone(), false)));
+                }
+            },
+        }
+        Ok(())
+    }
+
+    let mut words = Vec::new();
+    query_tree_words(ctx, query_tree, wdcache, &mut words)?;
+
+    let mut matched_fields = HashMap::new();
+    for field_id in ctx.searchable_fields_ids()? {
+        let left = field_id as u32 * ONE_ATTRIBUTE;
+        let right = left + MAX_COUNTED_POSITION;
+
+        let mut docids = RoaringBitmap::new();
+        for (word, in_prefix_cache) in &words {
+            let level = TreeLevel::min_value();
+            let iter =
+                ctx.word_position_iterator(word, level, *in_prefix_cache, Some(left), Some(right))?;
+            for result in iter {
+                let (_, word_docids) = result?;
+                docids |= word_docids;
+            }
+        }
+
+        docids &= candidates;
+        if !docids.is_empty() {
+            matched_fields.insert(field_id, docids);
+        }
+    }
+
+    Ok(matched_fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use heed::EnvOpenOptions;
+
+    use crate::update::{IndexDocuments, Settings, UpdateFormat};
+    use crate::Index;
+
+    #[test]
+    fn shorter_attributes_first() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_searchable_fields(vec![S("text")]);
+        builder.set_criteria(vec![S("words"), S("wordcount")]);
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "text": "once upon a time the quick brown fox jumped over the lazy dog" },
+            { "id": 2, "text": "the quick brown fox" },
+            { "id": 3, "text": "a quick brown fox jumped" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let result = index.search(&rtxn).query("quick brown fox").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn only_count_the_matched_fields() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_searchable_fields(vec![S("title"), S("text")]);
+        builder.set_criteria(vec![S("words"), S("wordcount")]);
+        builder.execute(|_, _| ()).unwrap();
+
+        // The short title of the first document doesn't contain the query words,
+        // it must not make this document better than the second one.
+        let content = &br#"[
+            { "id": 1, "title": "hello", "text": "the quick brown fox jumped over the lazy dog" },
+            { "id": 2, "title": "once upon a time in a land far away", "text": "the quick brown fox" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let result = index.search(&rtxn).query("quick brown fox").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![1, 0]);
+    }
+}
@@ -1,6 +1,6 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::ops::Bound::Unbounded;
-use std::{fmt, mem};
 
 use heed::types::ByteSlice;
 use roaring::RoaringBitmap;
@@ -20,13 +20,14 @@ const CANDIDATES_THRESHOLD: u64 = 3000;
 pub struct FacetDistribution<'a> {
     facets: Option<HashSet<String>>,
     candidates: Option<RoaringBitmap>,
+    original_values: bool,
     rtxn: &'a heed::RoTxn<'a>,
     index: &'a Index,
 }
 
 impl<'a> FacetDistribution<'a> {
     pub fn new(rtxn: &'a heed::RoTxn, index: &'a Index) -> FacetDistribution<'a> {
-        FacetDistribution { facets: None, candidates: None, rtxn, index }
+        FacetDistribution { facets: None, candidates: None, original_values: true, rtxn, index }
     }
 
     pub fn facets<I: IntoIterator<Item = A>, A: AsRef<str>>(&mut self, names: I) -> &mut Self {
@@ -39,60 +40,90 @@ impl<'a> FacetDistribution<'a> {
         self
     }
 
+    /// Whether the facet string values are returned as they were in the documents,
+    /// or in their normalized form (trimmed and lowercased), on which they are grouped.
+    /// default value if not called: `true`
+    pub fn with_original_values(&mut self, value: bool) -> &mut Self {
+        self.original_values = value;
+        self
+    }
+
+    fn facet_string_value(&self, normalized: &str, original: &str) -> String {
+        if self.original_values {
+            original.to_string()
+        } else {
+            normalized.to_string()
+        }
+    }
+
     /// There is a small amount of candidates OR we ask for facet string values so we
     /// decide to iterate over the facet values of each one of them, one by one.
-    fn facet_distribution_from_documents(
+    ///
+    /// The candidates are visited only once, the facet values of all the fields are
+    /// retrieved for each one of them.
+    fn facet_distributions_from_documents(
         &self,
-        field_id: FieldId,
+        fields_ids: &[FieldId],
         facet_type: FacetType,
         candidates: &RoaringBitmap,
-        distribution: &mut BTreeMap<String, u64>,
+        distributions: &mut HashMap<FieldId, BTreeMap<String, u64>>,
     ) -> heed::Result<()> {
+        let mut key_buffer = Vec::new();
         match facet_type {
             FacetType::Number => {
-                let mut key_buffer: Vec<_> = field_id.to_be_bytes().iter().copied().collect();
-
                 let db = self.index.field_id_docid_facet_f64s;
                 for docid in candidates.into_iter() {
-                    key_buffer.truncate(mem::size_of::<FieldId>());
-                    key_buffer.extend_from_slice(&docid.to_be_bytes());
-                    let iter = db
-                        .remap_key_type::<ByteSlice>()
-                        .prefix_iter(self.rtxn, &key_buffer)?
-                        .remap_key_type::<FieldDocIdFacetF64Codec>();
-
-                    for result in iter {
-                        let ((_, _, value), ()) = result?;
-                        *distribution.entry(value.to_string()).or_insert(0) += 1;
+                    for &field_id in fields_ids {
+                        key_buffer.clear();
+                        key_buffer.extend_from_slice(&field_id.to_be_bytes());
+                        key_buffer.extend_from_slice(&docid.to_be_bytes());
+                        let iter = db
+                            .remap_key_type::<ByteSlice>()
+                            .prefix_iter(self.rtxn, &key_buffer)?
+                            .remap_key_type::<FieldDocIdFacetF64Codec>();
+
+                        let distribution = distributions.entry(field_id).or_default();
+                        for result in iter {
+                            let ((_, _, value), ()) = result?;
+                            *distribution.entry(value.to_string()).or_insert(0) += 1;
+                        }
                     }
                 }
             }
             FacetType::String => {
-                let mut normalized_distribution = BTreeMap::new();
-                let mut key_buffer: Vec<_> = field_id.to_be_bytes().iter().copied().collect();
+                let mut normalized_distributions = HashMap::new();
 
                 let db = self.index.field_id_docid_facet_strings;
                 for docid in candidates.into_iter() {
-                    key_buffer.truncate(mem::size_of::<FieldId>());
-                    key_buffer.extend_from_slice(&docid.to_be_bytes());
-                    let iter = db
-                        .remap_key_type::<ByteSlice>()
-                        .prefix_iter(self.rtxn, &key_buffer)?
-                        .remap_key_type::<FieldDocIdFacetStringCodec>();
-
-                    for result in iter {
-                        let ((_, _, normalized_value), original_value) = result?;
-                        let (_, count) = normalized_distribution
-                            .entry(normalized_value)
-                            .or_insert_with(|| (original_value, 0));
-                        *count += 1;
+                    for &field_id in fields_ids {
+                        key_buffer.clear();
+                        key_buffer.extend_from_slice(&field_id.to_be_bytes());
+                        key_buffer.extend_from_slice(&docid.to_be_bytes());
+                        let iter = db
+                            .remap_key_type::<ByteSlice>()
+                            .prefix_iter(self.rtxn, &key_buffer)?
+                            .remap_key_type::<FieldDocIdFacetStringCodec>();
+
+                        let normalized_distribution =
+                            normalized_distributions.entry(field_id).or_insert_with(BTreeMap::new);
+                        for result in iter {
+                            let ((_, _, normalized_value), original_value) = result?;
+                            let (_, count) = normalized_distribution
+                                .entry(normalized_value)
+                                .or_insert_with(|| (original_value, 0));
+                            *count += 1;
+                        }
                     }
                 }
 
-                let iter = normalized_distribution
-                    .into_iter()
-                    .map(|(_normalized, (original, count))| (original.to_string(), count));
-                distribution.extend(iter);
+                for (field_id, normalized_distribution) in normalized_distributions {
+                    let iter = normalized_distribution.into_iter().map(
+                        |(normalized, (original, count))| {
+                            (self.facet_string_value(normalized, original), count)
+                        },
+                    );
+                    distributions.entry(field_id).or_default().extend(iter);
+                }
             }
         }
 
@@ -131,10 +162,10 @@ impl<'a> FacetDistribution<'a> {
             FacetStringIter::new_non_reducing(self.rtxn, self.index, field_id, candidates.clone())?;
 
         for result in iter {
-            let (_normalized, original, mut docids) = result?;
+            let (normalized, original, mut docids) = result?;
             docids &= candidates;
             if !docids.is_empty() {
-                distribution.insert(original.to_string(), docids.len());
+                distribution.insert(self.facet_string_value(normalized, original), docids.len());
             }
         }
 
@@ -170,36 +201,46 @@ impl<'a> FacetDistribution<'a> {
             normalized_distribution.insert(normalized_value, (original_value, docids.len()));
         }
 
-        let iter = normalized_distribution
-            .into_iter()
-            .map(|(_normalized, (original, count))| (original.to_string(), count));
+        let iter = normalized_distribution.into_iter().map(|(normalized, (original, count))| {
+            (self.facet_string_value(normalized, original), count)
+        });
         distribution.extend(iter);
 
         Ok(distribution)
     }
 
-    fn facet_values(&self, field_id: FieldId) -> heed::Result<BTreeMap<String, u64>> {
+    /// Returns the facet values of all the given fields.
+    ///
+    /// When there is a small amount of candidates, they are visited only once for all the
+    /// fields. Otherwise the facet values are stored by field, the facet levels or the raw
+    /// facet database of each field are then iterated one after the other.
+    fn facets_values(
+        &self,
+        fields_ids: &[FieldId],
+    ) -> heed::Result<HashMap<FieldId, BTreeMap<String, u64>>> {
         use FacetType::{Number, String};
 
+        let mut distributions = HashMap::new();
         match self.candidates {
-            Some(ref candidates) => {
+            Some(ref candidates) if candidates.len() <= CANDIDATES_THRESHOLD => {
                 // Classic search, candidates were specified, we must return facet values only related
                 // to those candidates. We also enter here for facet strings for performance reasons.
-                let mut distribution = BTreeMap::new();
-                if candidates.len() <= CANDIDATES_THRESHOLD {
-                    self.facet_distribution_from_documents(
-                        field_id,
-                        Number,
-                        candidates,
-                        &mut distribution,
-                    )?;
-                    self.facet_distribution_from_documents(
-                        field_id,
-                        String,
-                        candidates,
-                        &mut distribution,
-                    )?;
-                } else {
+                self.facet_distributions_from_documents(
+                    fields_ids,
+                    Number,
+                    candidates,
+                    &mut distributions,
+                )?;
+                self.facet_distributions_from_documents(
+                    fields_ids,
+                    String,
+                    candidates,
+                    &mut distributions,
+                )?;
+            }
+            Some(ref candidates) => {
+                for &field_id in fields_ids {
+                    let mut distribution = BTreeMap::new();
                     self.facet_numbers_distribution_from_facet_levels(
                         field_id,
                         candidates,
@@ -210,11 +251,18 @@ impl<'a> FacetDistribution<'a> {
                         candidates,
                         &mut distribution,
                     )?;
+                    distributions.insert(field_id, distribution);
+                }
+            }
+            None => {
+                for &field_id in fields_ids {
+                    let distribution = self.facet_values_from_raw_facet_database(field_id)?;
+                    distributions.insert(field_id, distribution);
                 }
-                Ok(distribution)
             }
-            None => self.facet_values_from_raw_facet_database(field_id),
         }
+
+        Ok(distributions)
     }
 
     pub fn execute(&self) -> Result<BTreeMap<String, BTreeMap<String, u64>>> {
@@ -235,25 +283,42 @@ impl<'a> FacetDistribution<'a> {
             None => filterable_fields,
         };
 
+        let fields: Vec<_> =
+            fields.into_iter().filter_map(|name| Some((fields_ids_map.id(&name)?, name))).collect();
+        let fields_ids: Vec<_> = fields.iter().map(|(fid, _)| *fid).collect();
+        let mut distributions = self.facets_values(&fields_ids)?;
+
         let mut distribution = BTreeMap::new();
-        for name in fields {
-            if let Some(fid) = fields_ids_map.id(&name) {
-                let values = self.facet_values(fid)?;
-                distribution.insert(name, values);
-            }
+        for (fid, name) in fields {
+            let values = distributions.remove(&fid).unwrap_or_default();
+            distribution.insert(name, values);
         }
 
         Ok(distribution)
     }
+
+    /// Returns the distributions of the given fields ids, unlike [`FacetDistribution::execute`]
+    /// the fields are not required to be filterable and the facets names are ignored.
+    pub(crate) fn execute_fields_ids(
+        &self,
+        fields_ids: &[FieldId],
+    ) -> Result<HashMap<FieldId, BTreeMap<String, u64>>> {
+        let mut distributions = self.facets_values(fields_ids)?;
+        for field_id in fields_ids {
+            distributions.entry(*field_id).or_default();
+        }
+        Ok(distributions)
+    }
 }
 
 impl fmt::Debug for FacetDistribution<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let FacetDistribution { facets, candidates, rtxn: _, index: _ } = self;
+        let FacetDistribution { facets, candidates, original_values, rtxn: _, index: _ } = self;
 
         f.debug_struct("FacetDistribution")
             .field("facets", facets)
             .field("candidates", candidates)
+            .field("original_values", original_values)
             .finish()
     }
 }
@@ -18,11 +18,21 @@ use self::Operator::*;
 use super::parser::{FilterParser, Rule, PREC_CLIMBER};
 use super::FacetNumberRange;
 use crate::error::UserError;
+use crate::facet::parse_date_millis;
 use crate::heed_codec::facet::{
     FacetLevelValueF64Codec, FacetStringLevelZeroCodec, FacetStringLevelZeroValueCodec,
 };
 use crate::{CboRoaringBitmapCodec, FieldId, FieldsIdsMap, Index, Result};
 
+#[cfg(test)]
+thread_local! {
+    /// The number of times the equality fast path has been taken by this thread.
+    static EQUAL_FAST_PATH_COUNT: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+/// The pseudo-field that can be used to filter on the internal documents ids.
+pub const DOCUMENT_ID_FIELD: &str = "_docid";
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
     GreaterThan(f64),
@@ -32,6 +42,14 @@ pub enum Operator {
     LowerThan(f64),
     LowerThanOrEqual(f64),
     Between(f64, f64),
+    /// The field is present in the document, whatever its value.
+    Exists,
+    /// The field is missing from the document.
+    NotExists,
+    /// The field is equal to one of the listed values, an empty list matches no document.
+    In(Vec<(Option<f64>, String)>),
+    /// The field is equal to none of the listed values, an empty list matches every document.
+    NotIn(Vec<(Option<f64>, String)>),
 }
 
 impl Operator {
@@ -46,6 +64,10 @@ impl Operator {
             LowerThan(n) => (GreaterThanOrEqual(n), None),
             LowerThanOrEqual(n) => (GreaterThan(n), None),
             Between(n, m) => (LowerThan(n), Some(GreaterThan(m))),
+            Exists => (NotExists, None),
+            NotExists => (Exists, None),
+            In(values) => (NotIn(values), None),
+            NotIn(values) => (In(values), None),
         }
     }
 }
@@ -53,6 +75,8 @@ impl Operator {
 #[derive(Debug, Clone, PartialEq)]
 pub enum FilterCondition {
     Operator(FieldId, Operator),
+    /// An operator applied to the internal documents ids, the `_docid` pseudo-field.
+    DocumentId(Operator),
     Or(Box<Self>, Box<Self>),
     And(Box<Self>, Box<Self>),
     Empty,
@@ -113,27 +137,75 @@ impl FilterCondition {
         let filterable_fields = index.filterable_fields(rtxn)?;
         let lexed =
             FilterParser::parse(Rule::prgm, expression).map_err(UserError::InvalidFilter)?;
-        FilterCondition::from_pairs(&fields_ids_map, &filterable_fields, lexed)
+        let date_fields = index.date_fields(rtxn)?;
+        FilterCondition::from_pairs(&fields_ids_map, &filterable_fields, &date_fields, lexed)
+    }
+
+    /// Parses the expression like [`FilterCondition::from_str`] does, but a condition on a
+    /// field that no document contains and that is not filterable is not an error: it
+    /// matches no document. The syntax errors and the conditions on fields that exist but
+    /// are not filterable are still reported.
+    ///
+    /// Note that the negation of such a condition matches every document.
+    pub fn from_str_lenient(
+        rtxn: &heed::RoTxn,
+        index: &Index,
+        expression: &str,
+    ) -> Result<FilterCondition> {
+        let fields_ids_map = index.fields_ids_map(rtxn)?;
+        let mut filterable_fields = index.filterable_fields(rtxn)?;
+        let lexed =
+            FilterParser::parse(Rule::prgm, expression).map_err(UserError::InvalidFilter)?;
+
+        // The unknown fields are considered filterable, they are not yet associated
+        // to a field id and the conditions on them are therefore always empty.
+        for pair in lexed.clone().flatten() {
+            match pair.as_rule() {
+                Rule::in_list
+                | Rule::exists
+                | Rule::not_exists
+                | Rule::between
+                | Rule::geq
+                | Rule::leq
+                | Rule::neq
+                | Rule::eq
+                | Rule::greater
+                | Rule::less => {
+                    let key = pair.into_inner().next().unwrap().as_str();
+                    if key != DOCUMENT_ID_FIELD && fields_ids_map.id(key).is_none() {
+                        filterable_fields.insert(key.to_string());
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let date_fields = index.date_fields(rtxn)?;
+        FilterCondition::from_pairs(&fields_ids_map, &filterable_fields, &date_fields, lexed)
     }
 
     fn from_pairs(
         fim: &FieldsIdsMap,
         ff: &HashSet<String>,
+        df: &HashSet<String>,
         expression: Pairs<Rule>,
     ) -> Result<Self> {
         PREC_CLIMBER.climb(
             expression,
             |pair: Pair<Rule>| match pair.as_rule() {
-                Rule::greater => Ok(Self::greater_than(fim, ff, pair)?),
-                Rule::geq => Ok(Self::greater_than_or_equal(fim, ff, pair)?),
-                Rule::eq => Ok(Self::equal(fim, ff, pair)?),
-                Rule::neq => Ok(Self::equal(fim, ff, pair)?.negate()),
-                Rule::leq => Ok(Self::lower_than_or_equal(fim, ff, pair)?),
-                Rule::less => Ok(Self::lower_than(fim, ff, pair)?),
-                Rule::between => Ok(Self::between(fim, ff, pair)?),
-                Rule::not => Ok(Self::from_pairs(fim, ff, pair.into_inner())?.negate()),
-                Rule::prgm => Self::from_pairs(fim, ff, pair.into_inner()),
-                Rule::term => Self::from_pairs(fim, ff, pair.into_inner()),
+                Rule::greater => Ok(Self::greater_than(fim, ff, df, pair)?),
+                Rule::geq => Ok(Self::greater_than_or_equal(fim, ff, df, pair)?),
+                Rule::eq => Ok(Self::equal(fim, ff, df, pair)?),
+                Rule::neq => Ok(Self::equal(fim, ff, df, pair)?.negate()),
+                Rule::leq => Ok(Self::lower_than_or_equal(fim, ff, df, pair)?),
+                Rule::less => Ok(Self::lower_than(fim, ff, df, pair)?),
+                Rule::between => Ok(Self::between(fim, ff, df, pair)?),
+                Rule::in_list => Ok(Self::in_list(fim, ff, df, pair)?),
+                Rule::exists => Ok(Self::exists(fim, ff, df, pair)?),
+                Rule::not_exists => Ok(Self::exists(fim, ff, df, pair)?.negate()),
+                Rule::not => Ok(Self::from_pairs(fim, ff, df, pair.into_inner())?.negate()),
+                Rule::prgm => Self::from_pairs(fim, ff, df, pair.into_inner()),
+                Rule::term => Self::from_pairs(fim, ff, df, pair.into_inner()),
                 _ => unreachable!(),
             },
             |lhs: Result<Self>, op: Pair<Rule>, rhs: Result<Self>| match op.as_rule() {
@@ -150,132 +222,187 @@ impl FilterCondition {
                 (op, None) => Operator(fid, op),
                 (a, Some(b)) => Or(Box::new(Operator(fid, a)), Box::new(Operator(fid, b))),
             },
+            DocumentId(op) => match op.negate() {
+                (op, None) => DocumentId(op),
+                (a, Some(b)) => Or(Box::new(DocumentId(a)), Box::new(DocumentId(b))),
+            },
             Or(a, b) => And(Box::new(a.negate()), Box::new(b.negate())),
             And(a, b) => Or(Box::new(a.negate()), Box::new(b.negate())),
-            Empty => Empty,
+            // The conditions on a field that no document contains are empty, their negation
+            // matches every document, like the `_docid` pseudo-field which always exists.
+            Empty => DocumentId(Exists),
         }
     }
 
     fn between(
         fields_ids_map: &FieldsIdsMap,
         filterable_fields: &HashSet<String>,
+        date_fields: &HashSet<String>,
         item: Pair<Rule>,
     ) -> Result<FilterCondition> {
         let mut items = item.into_inner();
-        let fid = match field_id(fields_ids_map, filterable_fields, &mut items)
+        let target = match filter_target(fields_ids_map, filterable_fields, date_fields, &mut items)
             .map_err(UserError::InvalidFilterAttribute)?
         {
-            Some(fid) => fid,
+            Some(target) => target,
             None => return Ok(Empty),
         };
 
-        let (lresult, _) = pest_parse(items.next().unwrap());
-        let (rresult, _) = pest_parse(items.next().unwrap());
+        let (lresult, _) = target.parse_number(items.next().unwrap());
+        let (rresult, _) = target.parse_number(items.next().unwrap());
 
         let lvalue = lresult.map_err(UserError::InvalidFilter)?;
         let rvalue = rresult.map_err(UserError::InvalidFilter)?;
 
-        Ok(Operator(fid, Between(lvalue, rvalue)))
+        Ok(target.with(Between(lvalue, rvalue)))
+    }
+
+    /// The `IN` operator is the union of the equalities with each of the listed values,
+    /// an empty list matches no document and its negation matches every document.
+    fn in_list(
+        fields_ids_map: &FieldsIdsMap,
+        filterable_fields: &HashSet<String>,
+        date_fields: &HashSet<String>,
+        item: Pair<Rule>,
+    ) -> Result<FilterCondition> {
+        let mut items = item.into_inner();
+        let target = match filter_target(fields_ids_map, filterable_fields, date_fields, &mut items)
+            .map_err(UserError::InvalidFilterAttribute)?
+        {
+            Some(target) => target,
+            None => return Ok(Empty),
+        };
+
+        let values = items
+            .map(|value| {
+                let (result, svalue) = target.parse_number(value);
+                (result.ok(), svalue.to_lowercase())
+            })
+            .collect();
+
+        Ok(target.with(In(values)))
+    }
+
+    fn exists(
+        fields_ids_map: &FieldsIdsMap,
+        filterable_fields: &HashSet<String>,
+        date_fields: &HashSet<String>,
+        item: Pair<Rule>,
+    ) -> Result<FilterCondition> {
+        let mut items = item.into_inner();
+        let target = match filter_target(fields_ids_map, filterable_fields, date_fields, &mut items)
+            .map_err(UserError::InvalidFilterAttribute)?
+        {
+            Some(target) => target,
+            None => return Ok(Empty),
+        };
+
+        Ok(target.with(Exists))
     }
 
     fn equal(
         fields_ids_map: &FieldsIdsMap,
         filterable_fields: &HashSet<String>,
+        date_fields: &HashSet<String>,
         item: Pair<Rule>,
     ) -> Result<FilterCondition> {
         let mut items = item.into_inner();
-        let fid = match field_id(fields_ids_map, filterable_fields, &mut items)
+        let target = match filter_target(fields_ids_map, filterable_fields, date_fields, &mut items)
             .map_err(UserError::InvalidFilterAttribute)?
         {
-            Some(fid) => fid,
+            Some(target) => target,
             None => return Ok(Empty),
         };
 
         let value = items.next().unwrap();
-        let (result, svalue) = pest_parse(value);
+        let (result, svalue) = target.parse_number(value);
 
         let svalue = svalue.to_lowercase();
-        Ok(Operator(fid, Equal(result.ok(), svalue)))
+        Ok(target.with(Equal(result.ok(), svalue)))
     }
 
     fn greater_than(
         fields_ids_map: &FieldsIdsMap,
         filterable_fields: &HashSet<String>,
+        date_fields: &HashSet<String>,
         item: Pair<Rule>,
     ) -> Result<FilterCondition> {
         let mut items = item.into_inner();
-        let fid = match field_id(fields_ids_map, filterable_fields, &mut items)
+        let target = match filter_target(fields_ids_map, filterable_fields, date_fields, &mut items)
             .map_err(UserError::InvalidFilterAttribute)?
         {
-            Some(fid) => fid,
+            Some(target) => target,
             None => return Ok(Empty),
         };
 
         let value = items.next().unwrap();
-        let (result, _svalue) = pest_parse(value);
+        let (result, _svalue) = target.parse_number(value);
         let value = result.map_err(UserError::InvalidFilter)?;
 
-        Ok(Operator(fid, GreaterThan(value)))
+        Ok(target.with(GreaterThan(value)))
     }
 
     fn greater_than_or_equal(
         fields_ids_map: &FieldsIdsMap,
         filterable_fields: &HashSet<String>,
+        date_fields: &HashSet<String>,
         item: Pair<Rule>,
     ) -> Result<FilterCondition> {
         let mut items = item.into_inner();
-        let fid = match field_id(fields_ids_map, filterable_fields, &mut items)
+        let target = match filter_target(fields_ids_map, filterable_fields, date_fields, &mut items)
             .map_err(UserError::InvalidFilterAttribute)?
         {
-            Some(fid) => fid,
+            Some(target) => target,
             None => return Ok(Empty),
         };
 
         let value = items.next().unwrap();
-        let (result, _svalue) = pest_parse(value);
+        let (result, _svalue) = target.parse_number(value);
         let value = result.map_err(UserError::InvalidFilter)?;
 
-        Ok(Operator(fid, GreaterThanOrEqual(value)))
+        Ok(target.with(GreaterThanOrEqual(value)))
     }
 
     fn lower_than(
         fields_ids_map: &FieldsIdsMap,
         filterable_fields: &HashSet<String>,
+        date_fields: &HashSet<String>,
         item: Pair<Rule>,
     ) -> Result<FilterCondition> {
         let mut items = item.into_inner();
-        let fid = match field_id(fields_ids_map, filterable_fields, &mut items)
+        let target = match filter_target(fields_ids_map, filterable_fields, date_fields, &mut items)
             .map_err(UserError::InvalidFilterAttribute)?
         {
-            Some(fid) => fid,
+            Some(target) => target,
             None => return Ok(Empty),
         };
 
         let value = items.next().unwrap();
-        let (result, _svalue) = pest_parse(value);
+        let (result, _svalue) = target.parse_number(value);
         let value = result.map_err(UserError::InvalidFilter)?;
 
-        Ok(Operator(fid, LowerThan(value)))
+        Ok(target.with(LowerThan(value)))
     }
 
     fn lower_than_or_equal(
         fields_ids_map: &FieldsIdsMap,
         filterable_fields: &HashSet<String>,
+        date_fields: &HashSet<String>,
         item: Pair<Rule>,
     ) -> Result<FilterCondition> {
         let mut items = item.into_inner();
-        let fid = match field_id(fields_ids_map, filterable_fields, &mut items)
+        let target = match filter_target(fields_ids_map, filterable_fields, date_fields, &mut items)
             .map_err(UserError::InvalidFilterAttribute)?
         {
-            Some(fid) => fid,
+            Some(target) => target,
             None => return Ok(Empty),
         };
 
         let value = items.next().unwrap();
-        let (result, _svalue) = pest_parse(value);
+        let (result, _svalue) = target.parse_number(value);
         let value = result.map_err(UserError::InvalidFilter)?;
 
-        Ok(Operator(fid, LowerThanOrEqual(value)))
+        Ok(target.with(LowerThanOrEqual(value)))
     }
 }
 
@@ -287,16 +414,16 @@ impl FilterCondition {
         db: heed::Database<FacetLevelValueF64Codec, CboRoaringBitmapCodec>,
         field_id: FieldId,
         level: u8,
-        left: Bound<f64>,
-        right: Bound<f64>,
+        range: LevelsRange,
         output: &mut RoaringBitmap,
     ) -> Result<()> {
+        let LevelsRange { left, right, budget } = range;
+
         match (left, right) {
             // If the request is an exact value we must go directly to the deepest level.
             (Included(l), Included(r)) if l == r && level > 0 => {
-                return Self::explore_facet_number_levels(
-                    rtxn, db, field_id, 0, left, right, output,
-                );
+                let range = LevelsRange { left, right, budget };
+                return Self::explore_facet_number_levels(rtxn, db, field_id, 0, range, output);
             }
             // lower TO upper when lower > upper must return no result
             (Included(l), Included(r)) if l > r => return Ok(()),
@@ -317,6 +444,7 @@ impl FilterCondition {
 
         for (i, result) in iter.enumerate() {
             let ((_fid, level, l, r), docids) = result?;
+            budget.scan(field_id)?;
             debug!("{:?} to {:?} (level {}) found {} documents", l, r, level, docids.len());
             *output |= docids;
             // We save the leftest and rightest bounds we actually found at this level.
@@ -343,13 +471,13 @@ impl FilterCondition {
                         "calling left with {:?} to {:?} (level {})",
                         left, sub_right, deeper_level
                     );
+                    let range = LevelsRange { left, right: sub_right, budget: &mut *budget };
                     Self::explore_facet_number_levels(
                         rtxn,
                         db,
                         field_id,
                         deeper_level,
-                        left,
-                        sub_right,
+                        range,
                         output,
                     )?;
                 }
@@ -359,13 +487,13 @@ impl FilterCondition {
                         "calling right with {:?} to {:?} (level {})",
                         sub_left, right, deeper_level
                     );
+                    let range = LevelsRange { left: sub_left, right, budget };
                     Self::explore_facet_number_levels(
                         rtxn,
                         db,
                         field_id,
                         deeper_level,
-                        sub_left,
-                        right,
+                        range,
                         output,
                     )?;
                 }
@@ -373,21 +501,43 @@ impl FilterCondition {
             None => {
                 // If we found nothing at this level it means that we must find
                 // the same bounds but at a deeper, more precise level.
-                Self::explore_facet_number_levels(
-                    rtxn,
-                    db,
-                    field_id,
-                    deeper_level,
-                    left,
-                    right,
-                    output,
-                )?;
+                let range = LevelsRange { left, right, budget };
+                Self::explore_facet_number_levels(rtxn, db, field_id, deeper_level, range, output)?;
             }
         }
 
         Ok(())
     }
 
+    /// An exact facet value is only stored in the level 0 of the facet databases, we can
+    /// therefore directly read the documents ids associated with it, without exploring the
+    /// facet levels. This makes a single equality filter a cheap starting candidates set.
+    ///
+    /// The value is still charged to the scan budget, once for both its string and number forms.
+    fn evaluate_equal(
+        rtxn: &heed::RoTxn,
+        numbers_db: heed::Database<FacetLevelValueF64Codec, CboRoaringBitmapCodec>,
+        strings_db: heed::Database<FacetStringLevelZeroCodec, FacetStringLevelZeroValueCodec>,
+        field_id: FieldId,
+        number: Option<f64>,
+        string: &str,
+        budget: &mut ScanBudget,
+    ) -> Result<RoaringBitmap> {
+        #[cfg(test)]
+        EQUAL_FAST_PATH_COUNT.with(|count| count.set(count.get() + 1));
+
+        budget.scan(field_id)?;
+
+        let (_original_value, string_docids) =
+            strings_db.get(rtxn, &(field_id, string))?.unwrap_or_default();
+        let number_docids = match number {
+            Some(n) => numbers_db.get(rtxn, &(field_id, 0, n, n))?.unwrap_or_default(),
+            None => RoaringBitmap::new(),
+        };
+
+        Ok(string_docids | number_docids)
+    }
+
     fn evaluate_operator(
         rtxn: &heed::RoTxn,
         index: &Index,
@@ -395,6 +545,7 @@ impl FilterCondition {
         strings_db: heed::Database<FacetStringLevelZeroCodec, FacetStringLevelZeroValueCodec>,
         field_id: FieldId,
         operator: &Operator,
+        budget: &mut ScanBudget,
     ) -> Result<RoaringBitmap> {
         // Make sure we always bound the ranges with the field id and the level,
         // as the facets values are all in the same database and prefixed by the
@@ -403,43 +554,51 @@ impl FilterCondition {
             GreaterThan(val) => (Excluded(*val), Included(f64::MAX)),
             GreaterThanOrEqual(val) => (Included(*val), Included(f64::MAX)),
             Equal(number, string) => {
-                let (_original_value, string_docids) =
-                    strings_db.get(rtxn, &(field_id, &string))?.unwrap_or_default();
-                let number_docids = match number {
-                    Some(n) => {
-                        let n = Included(*n);
-                        let mut output = RoaringBitmap::new();
-                        Self::explore_facet_number_levels(
-                            rtxn,
-                            numbers_db,
-                            field_id,
-                            0,
-                            n,
-                            n,
-                            &mut output,
-                        )?;
-                        output
-                    }
-                    None => RoaringBitmap::new(),
-                };
-                return Ok(string_docids | number_docids);
+                return Self::evaluate_equal(
+                    rtxn, numbers_db, strings_db, field_id, *number, string, budget,
+                );
             }
             NotEqual(number, string) => {
-                let all_numbers_ids = if number.is_some() {
-                    index.number_faceted_documents_ids(rtxn, field_id)?
-                } else {
-                    RoaringBitmap::new()
-                };
-                let all_strings_ids = index.string_faceted_documents_ids(rtxn, field_id)?;
+                // The documents that don't contain the field are not equal to the value.
+                let documents_ids = index.documents_ids(rtxn)?;
                 let operator = Equal(*number, string.clone());
                 let docids = Self::evaluate_operator(
-                    rtxn, index, numbers_db, strings_db, field_id, &operator,
+                    rtxn, index, numbers_db, strings_db, field_id, &operator, budget,
                 )?;
-                return Ok((all_numbers_ids | all_strings_ids) - docids);
+                return Ok(documents_ids - docids);
             }
             LowerThan(val) => (Included(f64::MIN), Excluded(*val)),
             LowerThanOrEqual(val) => (Included(f64::MIN), Included(*val)),
             Between(left, right) => (Included(*left), Included(*right)),
+            Exists => {
+                let all_numbers_ids = index.number_faceted_documents_ids(rtxn, field_id)?;
+                let all_strings_ids = index.string_faceted_documents_ids(rtxn, field_id)?;
+                return Ok(all_numbers_ids | all_strings_ids);
+            }
+            NotExists => {
+                let documents_ids = index.documents_ids(rtxn)?;
+                let docids = Self::evaluate_operator(
+                    rtxn, index, numbers_db, strings_db, field_id, &Exists, budget,
+                )?;
+                return Ok(documents_ids - docids);
+            }
+            In(values) => {
+                let mut output = RoaringBitmap::new();
+                for (number, string) in values {
+                    output |= Self::evaluate_equal(
+                        rtxn, numbers_db, strings_db, field_id, *number, string, budget,
+                    )?;
+                }
+                return Ok(output);
+            }
+            NotIn(values) => {
+                let documents_ids = index.documents_ids(rtxn)?;
+                let operator = In(values.clone());
+                let docids = Self::evaluate_operator(
+                    rtxn, index, numbers_db, strings_db, field_id, &operator, budget,
+                )?;
+                return Ok(documents_ids - docids);
+            }
         };
 
         // Ask for the biggest value that can exist for this specific field, if it exists
@@ -452,13 +611,13 @@ impl FilterCondition {
         match biggest_level {
             Some(level) => {
                 let mut output = RoaringBitmap::new();
+                let range = LevelsRange { left, right, budget };
                 Self::explore_facet_number_levels(
                     rtxn,
                     numbers_db,
                     field_id,
                     level,
-                    left,
-                    right,
+                    range,
                     &mut output,
                 )?;
                 Ok(output)
@@ -467,22 +626,113 @@ impl FilterCondition {
         }
     }
 
+    /// Evaluates an operator directly on the internal documents ids,
+    /// without the help of the facet databases.
+    fn evaluate_document_id_operator(
+        rtxn: &heed::RoTxn,
+        index: &Index,
+        operator: &Operator,
+    ) -> Result<RoaringBitmap> {
+        let documents_ids = index.documents_ids(rtxn)?;
+        let (left, right) = match operator {
+            GreaterThan(val) => (Excluded(*val), Included(f64::MAX)),
+            GreaterThanOrEqual(val) => (Included(*val), Included(f64::MAX)),
+            Equal(Some(val), _) => (Included(*val), Included(*val)),
+            Equal(None, _) => return Ok(RoaringBitmap::new()),
+            NotEqual(number, string) => {
+                let operator = Equal(*number, string.clone());
+                let docids = Self::evaluate_document_id_operator(rtxn, index, &operator)?;
+                return Ok(documents_ids - docids);
+            }
+            LowerThan(val) => (Included(f64::MIN), Excluded(*val)),
+            LowerThanOrEqual(val) => (Included(f64::MIN), Included(*val)),
+            Between(left, right) => (Included(*left), Included(*right)),
+            Exists => return Ok(documents_ids),
+            NotExists => return Ok(RoaringBitmap::new()),
+            In(values) => {
+                let mut output = RoaringBitmap::new();
+                for (number, string) in values {
+                    let operator = Equal(*number, string.clone());
+                    output |= Self::evaluate_document_id_operator(rtxn, index, &operator)?;
+                }
+                return Ok(output);
+            }
+            NotIn(values) => {
+                let operator = In(values.clone());
+                let docids = Self::evaluate_document_id_operator(rtxn, index, &operator)?;
+                return Ok(documents_ids - docids);
+            }
+        };
+
+        // We convert the bounds into an exclusive range of valid documents ids.
+        let max = u32::MAX as f64 + 1.0;
+        let start = match left {
+            Included(val) => val.ceil(),
+            Excluded(val) => val.floor() + 1.0,
+            Bound::Unbounded => 0.0,
+        };
+        let end = match right {
+            Included(val) => val.floor() + 1.0,
+            Excluded(val) => val.ceil(),
+            Bound::Unbounded => max,
+        };
+        let (start, end) = (start.max(0.0).min(max) as u64, end.max(0.0).min(max) as u64);
+
+        let mut output = RoaringBitmap::new();
+        if start < end {
+            output.insert_range(start..end);
+        }
+
+        Ok(output & documents_ids)
+    }
+
     pub fn evaluate(&self, rtxn: &heed::RoTxn, index: &Index) -> Result<RoaringBitmap> {
+        let mut budget = ScanBudget { limit: None, scanned: 0, fields_ids_map: None };
+        self.evaluate_with_budget(rtxn, index, &mut budget)
+    }
+
+    /// Evaluates the filter like [`FilterCondition::evaluate`] does but returns a
+    /// [`UserError::FilterTooExpensive`] error as soon as more than `max_scanned`
+    /// facet values have been read from the facet databases.
+    ///
+    /// This is useful to protect against pathological filters on high-cardinality facets.
+    pub fn evaluate_with_scan_limit(
+        &self,
+        rtxn: &heed::RoTxn,
+        index: &Index,
+        max_scanned: usize,
+    ) -> Result<RoaringBitmap> {
+        let fields_ids_map = index.fields_ids_map(rtxn)?;
+        let mut budget = ScanBudget {
+            limit: Some(max_scanned),
+            scanned: 0,
+            fields_ids_map: Some(fields_ids_map),
+        };
+        self.evaluate_with_budget(rtxn, index, &mut budget)
+    }
+
+    fn evaluate_with_budget(
+        &self,
+        rtxn: &heed::RoTxn,
+        index: &Index,
+        budget: &mut ScanBudget,
+    ) -> Result<RoaringBitmap> {
         let numbers_db = index.facet_id_f64_docids;
         let strings_db = index.facet_id_string_docids;
 
         match self {
             Operator(fid, op) => {
-                Self::evaluate_operator(rtxn, index, numbers_db, strings_db, *fid, op)
+                Self::evaluate_operator(rtxn, index, numbers_db, strings_db, *fid, op, budget)
             }
+            DocumentId(op) => Self::evaluate_document_id_operator(rtxn, index, op),
             Or(lhs, rhs) => {
-                let lhs = lhs.evaluate(rtxn, index)?;
-                let rhs = rhs.evaluate(rtxn, index)?;
+                let lhs = lhs.evaluate_with_budget(rtxn, index, budget)?;
+                let rhs = rhs.evaluate_with_budget(rtxn, index, budget)?;
                 Ok(lhs | rhs)
             }
             And(lhs, rhs) => {
-                let lhs = lhs.evaluate(rtxn, index)?;
-                let rhs = rhs.evaluate(rtxn, index)?;
+                let lhs = lhs.evaluate_with_budget(rtxn, index, budget)?;
+                let rhs = rhs.evaluate_with_budget(rtxn, index, budget)?;
                 Ok(lhs & rhs)
             }
             Empty => Ok(RoaringBitmap::new()),
@@ -490,6 +740,93 @@ impl FilterCondition {
     }
 }
 
+/// The bounds of a range explored in the facet number levels, along with
+/// the budget that the facet values read are charged to.
+struct LevelsRange<'b> {
+    left: Bound<f64>,
+    right: Bound<f64>,
+    budget: &'b mut ScanBudget,
+}
+
+/// Keeps track of the number of facet values read while evaluating a filter.
+struct ScanBudget {
+    limit: Option<usize>,
+    scanned: usize,
+    fields_ids_map: Option<FieldsIdsMap>,
+}
+
+impl ScanBudget {
+    fn scan(&mut self, field_id: FieldId) -> Result<()> {
+        self.scanned += 1;
+        match self.limit {
+            Some(limit) if self.scanned > limit => {
+                let field = self
+                    .fields_ids_map
+                    .as_ref()
+                    .and_then(|map| map.name(field_id))
+                    .map_or_else(|| field_id.to_string(), ToString::to_string);
+                Err(UserError::FilterTooExpensive { field, scanned: self.scanned }.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The target of an operator, either a field or the internal documents ids.
+#[derive(Clone, Copy)]
+enum FilterTarget {
+    Field(FieldId),
+    /// A field declared as a date field, its facet numbers are milliseconds since the epoch.
+    DateField(FieldId),
+    DocumentId,
+}
+
+impl FilterTarget {
+    fn with(self, operator: Operator) -> FilterCondition {
+        match self {
+            FilterTarget::Field(fid) | FilterTarget::DateField(fid) => Operator(fid, operator),
+            FilterTarget::DocumentId => DocumentId(operator),
+        }
+    }
+
+    /// Parses the pest pair into a facet number like [`pest_parse`] does, when the target
+    /// is a date field the dates are also accepted and converted into the milliseconds since
+    /// the epoch, the date facets being indexed as such numbers. The dates are never accepted
+    /// for the other targets, where they would silently be compared with unrelated numbers.
+    fn parse_number(&self, pair: Pair<Rule>) -> (StdResult<f64, PestError<Rule>>, String) {
+        match (self, pest_parse(pair)) {
+            (FilterTarget::DateField(_), (Err(error), svalue)) => {
+                match parse_date_millis(&svalue) {
+                    Some(millis) => (Ok(millis as f64), svalue),
+                    None => (Err(error), svalue),
+                }
+            }
+            (_, (result, svalue)) => (result, svalue),
+        }
+    }
+}
+
+/// Retrieve the target of the operator based on the pest value, the `_docid`
+/// pseudo-field is always available, other fields must be filterable.
+fn filter_target(
+    fields_ids_map: &FieldsIdsMap,
+    filterable_fields: &HashSet<String>,
+    date_fields: &HashSet<String>,
+    items: &mut Pairs<Rule>,
+) -> StdResult<Option<FilterTarget>, PestError<Rule>> {
+    let key = items.clone().next().map(|key| key.as_str());
+    if key == Some(DOCUMENT_ID_FIELD) {
+        items.next();
+        return Ok(Some(FilterTarget::DocumentId));
+    }
+
+    let is_date = key.map_or(false, |key| date_fields.contains(key));
+    field_id(fields_ids_map, filterable_fields, items).map(|fid| match is_date {
+        true => fid.map(FilterTarget::DateField),
+        false => fid.map(FilterTarget::Field),
+    })
+}
+
 /// Retrieve the field id base on the pest value.
 ///
 /// Returns an error if the given value is not filterable.
@@ -549,7 +886,7 @@ mod tests {
     use maplit::hashset;
 
     use super::*;
-    use crate::update::Settings;
+    use crate::update::{IndexDocuments, Settings, UpdateFormat};
 
     #[test]
     fn string() {
@@ -698,4 +1035,443 @@ mod tests {
         .unwrap();
         assert_eq!(condition, expected);
     }
+
+    #[test]
+    fn document_id() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": 0 }, { "id": 1 }, { "id": 2 }, { "id": 3 }, { "id": 4 },
+            { "id": 5 }, { "id": 6 }, { "id": 7 }, { "id": 8 }, { "id": 9 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // The pseudo-field doesn't need to be filterable.
+        let rtxn = index.read_txn().unwrap();
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "_docid >= 2 AND _docid < 5").unwrap();
+        let expected = And(
+            Box::new(DocumentId(Operator::GreaterThanOrEqual(2.0))),
+            Box::new(DocumentId(Operator::LowerThan(5.0))),
+        );
+        assert_eq!(condition, expected);
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "_docid = 7").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![7]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "_docid 3 TO 5").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![3, 4, 5]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "NOT _docid > 1").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 1]);
+
+        // Out of range values do not select non-existing documents.
+        let condition = FilterCondition::from_str(&rtxn, &index, "_docid >= 8").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![8, 9]);
+    }
+
+    #[test]
+    fn equality_fast_path() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("region"), S("zone") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 0, "region": "europe", "zone": 1 },
+            { "id": 1, "region": "asia", "zone": 2 },
+            { "id": 2, "region": "Europe", "zone": 3 },
+            { "id": 3, "region": "america", "zone": 1 },
+            { "id": 4, "region": "europe", "zone": 1.5 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        EQUAL_FAST_PATH_COUNT.with(|count| count.set(0));
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "region = europe").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 2, 4]);
+        assert_eq!(EQUAL_FAST_PATH_COUNT.with(|count| count.get()), 1);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "zone = 1").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(EQUAL_FAST_PATH_COUNT.with(|count| count.get()), 2);
+
+        // The range operators do not take this path.
+        let condition = FilterCondition::from_str(&rtxn, &index, "zone >= 1.5").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![1, 2, 4]);
+        assert_eq!(EQUAL_FAST_PATH_COUNT.with(|count| count.get()), 2);
+    }
+
+    #[test]
+    fn lenient_unknown_field() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("channel") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 0, "channel": "ponce", "title": "hello" },
+            { "id": 1, "channel": "gotaga", "title": "world" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+
+        // By default a condition on an unknown field is an error.
+        FilterCondition::from_str(&rtxn, &index, "unknown = 12").unwrap_err();
+
+        // In lenient mode it matches no document.
+        let condition = FilterCondition::from_str_lenient(&rtxn, &index, "unknown = 12").unwrap();
+        assert_eq!(condition, Empty);
+        let condition =
+            FilterCondition::from_str_lenient(&rtxn, &index, "channel = ponce OR unknown > 12")
+                .unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0]);
+
+        // And its negation matches every document.
+        let condition = FilterCondition::from_str_lenient(&rtxn, &index, "unknown != 12").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 1]);
+
+        // Existing fields that are not filterable and syntax errors are still reported.
+        FilterCondition::from_str_lenient(&rtxn, &index, "title = hello").unwrap_err();
+        FilterCondition::from_str_lenient(&rtxn, &index, "unknown = ").unwrap_err();
+    }
+
+    #[test]
+    fn scan_limit() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("price") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let documents: Vec<_> =
+            (0..100).map(|i| serde_json::json!({ "id": i, "price": i })).collect();
+        let content = serde_json::to_vec(&documents).unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(&content[..], |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let condition = FilterCondition::from_str(&rtxn, &index, "price 10 TO 89").unwrap();
+
+        // Without any limit all the values are read.
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.len(), 80);
+        let docids = condition.evaluate_with_scan_limit(&rtxn, &index, usize::MAX).unwrap();
+        assert_eq!(docids.len(), 80);
+
+        match condition.evaluate_with_scan_limit(&rtxn, &index, 3) {
+            Err(crate::Error::UserError(UserError::FilterTooExpensive { field, scanned })) => {
+                assert_eq!(field, "price");
+                assert_eq!(scanned, 4);
+            }
+            otherwise => panic!("expected a too expensive filter error, got {:?}", otherwise),
+        }
+
+        // The equalities, even negated, are also charged to the budget.
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "price = 10 OR price != 20").unwrap();
+        match condition.evaluate_with_scan_limit(&rtxn, &index, 1) {
+            Err(crate::Error::UserError(UserError::FilterTooExpensive { field, scanned })) => {
+                assert_eq!(field, "price");
+                assert_eq!(scanned, 2);
+            }
+            otherwise => panic!("expected a too expensive filter error, got {:?}", otherwise),
+        }
+        let docids = condition.evaluate_with_scan_limit(&rtxn, &index, 2).unwrap();
+        assert_eq!(docids.len(), 99);
+    }
+
+    #[test]
+    fn in_list() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("code") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 0, "code": 12 },
+            { "id": 1, "code": "AB" },
+            { "id": 2, "code": "new, york" },
+            { "id": 3, "code": 13 },
+            { "id": 4, "code": "cd" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let fid = index.fields_ids_map(&rtxn).unwrap().id("code").unwrap();
+        let condition = FilterCondition::from_str(&rtxn, &index, "code IN [12, ab]").unwrap();
+        let expected = Operator(fid, Operator::In(vec![(Some(12.0), S("12")), (None, S("ab"))]));
+        assert_eq!(condition, expected);
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 1]);
+
+        // The quoted values can contain commas.
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, r#"code IN ["new, york", 13]"#).unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![2, 3]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "NOT code IN [12, ab]").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        // An empty list matches no document, and its negation matches all of them.
+        let condition = FilterCondition::from_str(&rtxn, &index, "code IN []").unwrap();
+        assert_eq!(condition, Operator(fid, Operator::In(vec![])));
+        assert!(condition.evaluate(&rtxn, &index).unwrap().is_empty());
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "NOT code IN []").unwrap();
+        assert_eq!(condition, Operator(fid, Operator::NotIn(vec![])));
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+        // Every listed value is charged to the scan budget.
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "code IN [12, ab, cd, 13]").unwrap();
+        match condition.evaluate_with_scan_limit(&rtxn, &index, 3) {
+            Err(crate::Error::UserError(UserError::FilterTooExpensive { field, .. })) => {
+                assert_eq!(field, "code");
+            }
+            otherwise => panic!("expected a filter too expensive error, got {:?}", otherwise),
+        }
+        let docids = condition.evaluate_with_scan_limit(&rtxn, &index, 4).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 1, 3, 4]);
+    }
+
+    #[test]
+    fn dates() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("created"), S("price") });
+        builder.set_date_fields(hashset! { S("created") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 0, "created": "2020-12-31", "price": 10 },
+            { "id": 1, "created": "2021-01-01T00:00:00Z", "price": 2021 },
+            { "id": 2, "created": "2021-03-15 12:30:00", "price": 15 },
+            { "id": 3, "created": "2021-07-01T02:00:00+02:00", "price": 20 },
+            { "id": 4, "created": "2021-07-01T02:00:01+02:00", "price": 1609459200000 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let condition = FilterCondition::from_str(&rtxn, &index, "created >= 2021-01-01").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "created 2021-01-02 TO 2021-07-01").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![2, 3]);
+
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "created = '2021-07-01T00:00:00'").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![3]);
+
+        // The dates are only accepted for the date fields, the other numbers are not affected.
+        let condition = FilterCondition::from_str(&rtxn, &index, "price 10 TO 2021").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        let result = FilterCondition::from_str(&rtxn, &index, "price 2021-01-01 TO 2021-07-01");
+        assert!(matches!(result, Err(crate::Error::UserError(UserError::InvalidFilter(_)))));
+        let result = FilterCondition::from_str(&rtxn, &index, "price >= 2021-01-01");
+        assert!(matches!(result, Err(crate::Error::UserError(UserError::InvalidFilter(_)))));
+        drop(rtxn);
+
+        // The documents with a value that is not a date are rejected.
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[{ "id": 5, "created": "yesterday" }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Json);
+        match builder.execute(content, |_, _| ()) {
+            Err(crate::Error::UserError(UserError::InvalidDateFacetValue { field, value })) => {
+                assert_eq!(field, "created");
+                assert_eq!(value, "yesterday");
+            }
+            otherwise => panic!("expected an invalid date error, got {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn exists() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("discount"), S("channel"), S("color") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 0, "discount": 10, "channel": "ponce" },
+            { "id": 1, "title": "hello", "channel": "gotaga" },
+            { "id": 2, "discount": "none" },
+            { "id": 3, "title": "world" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let fid = index.fields_ids_map(&rtxn).unwrap().id("discount").unwrap();
+        let condition = FilterCondition::from_str(&rtxn, &index, "discount EXISTS").unwrap();
+        assert_eq!(condition, Operator(fid, Operator::Exists));
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 2]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "discount NOT EXISTS").unwrap();
+        assert_eq!(condition, Operator(fid, Operator::NotExists));
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![1, 3]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "NOT discount EXISTS").unwrap();
+        assert_eq!(condition, Operator(fid, Operator::NotExists));
+
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "discount NOT EXISTS AND channel EXISTS")
+                .unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![1]);
+
+        // A filterable field that no document contains yet is missing from all of them.
+        let condition = FilterCondition::from_str(&rtxn, &index, "color EXISTS").unwrap();
+        assert!(condition.evaluate(&rtxn, &index).unwrap().is_empty());
+
+        for filter in &["color NOT EXISTS", "NOT color EXISTS", "color != red", "NOT color > 2"] {
+            let condition = FilterCondition::from_str(&rtxn, &index, filter).unwrap();
+            let docids = condition.evaluate(&rtxn, &index).unwrap();
+            assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3], "{}", filter);
+        }
+
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "NOT (color NOT EXISTS) OR discount = 10")
+                .unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0]);
+
+        // The field must be filterable.
+        match FilterCondition::from_str(&rtxn, &index, "title EXISTS") {
+            Err(crate::Error::UserError(UserError::InvalidFilterAttribute(error))) => {
+                assert!(error.to_string().contains("attribute `title` is not filterable"));
+            }
+            otherwise => panic!("expected an invalid filter attribute error, got {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn not_equal() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("category"), S("price") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 0, "category": "archived", "price": 10 },
+            { "id": 1, "category": "draft", "price": 20 },
+            { "id": 2, "price": 10 },
+            { "id": 3, "category": "Archived" },
+            { "id": 4, "title": "hello" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+
+        // The documents without the field are not equal to the value.
+        let condition = FilterCondition::from_str(&rtxn, &index, "category != archived").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![1, 2, 4]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "price != 10").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![1, 3, 4]);
+
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "category != archived AND price != 10")
+                .unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![1, 4]);
+
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "category != draft OR price = 20").unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "(category != archived) AND price EXISTS")
+                .unwrap();
+        let docids = condition.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(docids.iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
 }
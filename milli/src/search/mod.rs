@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 use std::collections::hash_map::{Entry, HashMap};
+use std::collections::BTreeSet;
 use std::fmt;
 use std::mem::take;
 use std::result::Result as StdResult;
@@ -13,15 +14,18 @@ use log::debug;
 use meilisearch_tokenizer::{Analyzer, AnalyzerConfig};
 use once_cell::sync::Lazy;
 use roaring::bitmap::RoaringBitmap;
+use serde_json::Value;
 
 pub(crate) use self::facet::ParserRule;
 pub use self::facet::{FacetDistribution, FacetNumberIter, FilterCondition, Operator};
 pub use self::matching_words::MatchingWords;
-use self::query_tree::QueryTreeBuilder;
+pub use self::query_tree::QueryTerm;
+use self::query_tree::{Operation, PrimitiveQuery, QueryTreeBuilder};
 use crate::criterion::{AscDesc, Criterion};
 use crate::error::UserError;
+use crate::proximity::ONE_ATTRIBUTE;
 use crate::search::criteria::r#final::{Final, FinalResult};
-use crate::{DocumentId, Index, Result};
+use crate::{obkv_to_json, DocumentId, FieldId, Index, Result};
 
 // Building these factories is not free.
 static LEVDIST0: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(0, true));
@@ -36,13 +40,25 @@ mod query_tree;
 
 pub struct Search<'a> {
     query: Option<String>,
+    query_terms: Option<Vec<QueryTerm>>,
+    match_all: bool,
+    empty_query_matches_nothing: bool,
     filter: Option<FilterCondition>,
     offset: usize,
     limit: usize,
     sort_criteria: Option<Vec<AscDesc>>,
     optional_words: bool,
     authorize_typos: bool,
+    min_word_len_one_typo: Option<u8>,
+    min_word_len_two_typos: Option<u8>,
     words_limit: usize,
+    infix_matching: bool,
+    min_word_match_ratio: Option<f32>,
+    exact_only: bool,
+    matched_fields: bool,
+    language: Option<String>,
+    deadline: Option<Instant>,
+    fail_on_timeout: bool,
     rtxn: &'a heed::RoTxn<'a>,
     index: &'a Index,
 }
@@ -51,13 +67,25 @@ impl<'a> Search<'a> {
     pub fn new(rtxn: &'a heed::RoTxn, index: &'a Index) -> Search<'a> {
         Search {
             query: None,
+            query_terms: None,
+            match_all: false,
+            empty_query_matches_nothing: false,
             filter: None,
             offset: 0,
             limit: 20,
             sort_criteria: None,
             optional_words: true,
             authorize_typos: true,
+            min_word_len_one_typo: None,
+            min_word_len_two_typos: None,
             words_limit: 10,
+            infix_matching: false,
+            min_word_match_ratio: None,
+            exact_only: false,
+            matched_fields: false,
+            language: None,
+            deadline: None,
+            fail_on_timeout: false,
             rtxn,
             index,
         }
@@ -68,6 +96,30 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Use an already split list of words and phrases as the query, instead of tokenizing
+    /// a query string. These terms take precedence over the query string.
+    pub fn query_terms(&mut self, terms: Vec<QueryTerm>) -> &mut Search<'a> {
+        self.query_terms = Some(terms);
+        self
+    }
+
+    /// Returns all the documents, only restricted by the filter if any, and ranked by the
+    /// sort and the asc/desc ranking rules. The query string and terms are ignored.
+    ///
+    /// Not specifying any query, or an empty query string, also returns all the documents.
+    pub fn match_all(&mut self) -> &mut Search<'a> {
+        self.match_all = true;
+        self
+    }
+
+    /// When enabled, a query string only made of whitespaces matches no document instead
+    /// of all of them, the explicit [`Search::match_all`] mode still returns all of them.
+    /// default value if not called: `false`
+    pub fn empty_query_matches_nothing(&mut self, value: bool) -> &mut Search<'a> {
+        self.empty_query_matches_nothing = value;
+        self
+    }
+
     pub fn offset(&mut self, offset: usize) -> &mut Search<'a> {
         self.offset = offset;
         self
@@ -93,39 +145,89 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// The query words shorter than this number of bytes must match without any typo,
+    /// by default the words of at least 5 bytes can match with one typo.
+    pub fn min_word_len_one_typo(&mut self, value: u8) -> &mut Search<'a> {
+        self.min_word_len_one_typo = Some(value);
+        self
+    }
+
+    /// The query words shorter than this number of bytes can match with at most one typo,
+    /// by default the words of at least 9 bytes can match with two typos.
+    pub fn min_word_len_two_typos(&mut self, value: u8) -> &mut Search<'a> {
+        self.min_word_len_two_typos = Some(value);
+        self
+    }
+
     pub fn words_limit(&mut self, value: usize) -> &mut Search<'a> {
         self.words_limit = value;
         self
     }
 
+    /// Also matches the words that contain the query words, e.g. `york` matches `newyork`.
+    ///
+    /// The whole words dictionary is scanned for every query word, it is expensive
+    /// and should only be enabled on indexes with a small vocabulary (e.g. product codes).
+    pub fn infix_matching(&mut self, value: bool) -> &mut Search<'a> {
+        self.infix_matching = value;
+        self
+    }
+
+    /// Requires the documents to match at least this ratio of the query words, between
+    /// `0.0` and `1.0`, the `Words` criterion only drops the last words down to this ratio.
+    /// This has no effect when the words are not optional.
+    pub fn min_word_match_ratio(&mut self, ratio: f32) -> &mut Search<'a> {
+        self.min_word_match_ratio = Some(ratio);
+        self
+    }
+
+    /// Only matches the documents containing all the query words exactly, without any typo,
+    /// prefix or synonym, this is useful to look up identifiers like SKUs or ISBNs.
+    pub fn exact_only(&mut self, value: bool) -> &mut Search<'a> {
+        self.exact_only = value;
+        self
+    }
+
+    /// Returns, for each returned document, the fields in which a query word matched.
+    pub fn matched_fields(&mut self, value: bool) -> &mut Search<'a> {
+        self.matched_fields = value;
+        self
+    }
+
+    /// Selects the stop words registered for this language, if any.
+    pub fn language(&mut self, language: impl Into<String>) -> &mut Search<'a> {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Stops ranking the documents once the deadline is exceeded, the deadline is checked
+    /// between the buckets of the ranking rules. The documents found so far are returned
+    /// and the result is marked as timed out.
+    pub fn deadline(&mut self, deadline: Instant) -> &mut Search<'a> {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Returns a [`UserError::SearchTimedOut`] error instead of the partial
+    /// results when the deadline is exceeded.
+    pub fn fail_on_timeout(&mut self, value: bool) -> &mut Search<'a> {
+        self.fail_on_timeout = value;
+        self
+    }
+
     pub fn filter(&mut self, condition: FilterCondition) -> &mut Search<'a> {
         self.filter = Some(condition);
         self
     }
 
     pub fn execute(&self) -> Result<SearchResult> {
+        if self.is_empty_query() {
+            return Ok(SearchResult::default());
+        }
+
         // We create the query tree by spliting the query into tokens.
         let before = Instant::now();
-        let (query_tree, primitive_query) = match self.query.as_ref() {
-            Some(query) => {
-                let mut builder = QueryTreeBuilder::new(self.rtxn, self.index);
-                builder.optional_words(self.optional_words);
-                builder.authorize_typos(self.authorize_typos);
-                builder.words_limit(self.words_limit);
-                // We make sure that the analyzer is aware of the stop words
-                // this ensures that the query builder is able to properly remove them.
-                let mut config = AnalyzerConfig::default();
-                let stop_words = self.index.stop_words(self.rtxn)?;
-                if let Some(ref stop_words) = stop_words {
-                    config.stop_words(stop_words);
-                }
-                let analyzer = Analyzer::new(config);
-                let result = analyzer.analyze(query);
-                let tokens = result.tokens();
-                builder.build(tokens)?.map_or((None, None), |(qt, pq)| (Some(qt), Some(pq)))
-            }
-            None => (None, None),
-        };
+        let (query_tree, primitive_query) = self.query_tree()?;
 
         debug!("query tree: {:?} took {:.02?}", query_tree, before.elapsed());
 
@@ -190,6 +292,109 @@ impl<'a> Search<'a> {
         }
     }
 
+    /// Returns the number of documents matching the query and the filter, the query tree
+    /// is resolved into a bitmap of candidates but the documents are not ranked.
+    ///
+    /// This is an upper bound of the number of relevant documents, the distinct attribute,
+    /// for example, is not applied.
+    pub fn estimate_candidates(&self) -> Result<u64> {
+        if self.is_empty_query() {
+            return Ok(0);
+        }
+
+        let (query_tree, _) = self.query_tree()?;
+        let criteria_builder = criteria::CriteriaBuilder::new(self.rtxn, self.index)?;
+        let mut candidates = match query_tree {
+            Some(query_tree) => {
+                let mut wdcache = WordDerivationsCache::new();
+                criteria::resolve_query_tree(&criteria_builder, &query_tree, &mut wdcache)?
+            }
+            None => self.index.documents_ids(self.rtxn)?,
+        };
+
+        if let Some(condition) = &self.filter {
+            candidates &= condition.evaluate(self.rtxn, self.index)?;
+        }
+
+        Ok(candidates.len())
+    }
+
+    /// Whether the query is only made of whitespaces and must match nothing.
+    fn is_empty_query(&self) -> bool {
+        if !self.empty_query_matches_nothing {
+            return false;
+        }
+        let query = if self.match_all { None } else { self.query.as_ref() };
+        let query_terms = if self.match_all { None } else { self.query_terms.as_ref() };
+        query_terms.is_none() && query.map_or(false, |query| query.trim().is_empty())
+    }
+
+    /// Builds the query tree from the query terms or by spliting the query string into tokens.
+    fn query_tree(&self) -> Result<(Option<Operation>, Option<PrimitiveQuery>)> {
+        let query = if self.match_all { None } else { self.query.as_ref() };
+        let query_terms = if self.match_all { None } else { self.query_terms.as_ref() };
+        let mut builder = QueryTreeBuilder::new(self.rtxn, self.index);
+        builder.optional_words(self.optional_words);
+        builder.authorize_typos(self.authorize_typos);
+        if let Some(min_len) = self.min_word_len_one_typo {
+            builder.min_word_len_one_typo(min_len);
+        }
+        if let Some(min_len) = self.min_word_len_two_typos {
+            builder.min_word_len_two_typos(min_len);
+        }
+        builder.words_limit(self.words_limit);
+        builder.infix_matching(self.infix_matching);
+        builder.exact_only(self.exact_only);
+        if let Some(ratio) = self.min_word_match_ratio {
+            builder.min_word_match_ratio(ratio);
+        }
+        if let Some(language) = &self.language {
+            builder.language(language.as_str());
+        }
+
+        let result = match (query_terms, query) {
+            (Some(terms), _) => builder.build_from_terms(terms)?,
+            (None, Some(query)) => {
+                // We make sure that the analyzer is aware of the stop words
+                // this ensures that the query builder is able to properly remove them.
+                let mut config = AnalyzerConfig::default();
+                let stop_words =
+                    self.index.stop_words_for_language(self.rtxn, self.language.as_deref())?;
+                if let Some(ref stop_words) = stop_words {
+                    config.stop_words(stop_words);
+                }
+                let analyzer = Analyzer::new(config);
+                let result = analyzer.analyze(query);
+                let tokens = result.tokens();
+                builder.build(tokens)?
+            }
+            (None, None) => None,
+        };
+
+        Ok(result.map_or((None, None), |(qt, pq)| (Some(qt), Some(pq))))
+    }
+
+    /// Executes the search and retrieves the documents associated with the results, converted
+    /// into JSON objects, returning a result that does not borrow the transaction.
+    pub fn execute_owned(&self) -> Result<OwnedSearchResult> {
+        let SearchResult { matching_words, candidates, documents_ids, matched_fields, timed_out } =
+            self.execute()?;
+
+        let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let displayed_fields = match self.index.displayed_fields_ids(self.rtxn)? {
+            Some(fields) => fields,
+            None => fields_ids_map.ids().collect(),
+        };
+
+        let mut documents = Vec::with_capacity(documents_ids.len());
+        for (id, obkv) in self.index.documents(self.rtxn, documents_ids)? {
+            let object = obkv_to_json(&displayed_fields, &fields_ids_map, obkv)?;
+            documents.push((id, Value::Object(object)));
+        }
+
+        Ok(OwnedSearchResult { matching_words, candidates, documents, matched_fields, timed_out })
+    }
+
     fn perform_sort<D: Distinct>(
         &self,
         mut distinct: D,
@@ -200,10 +405,23 @@ impl<'a> Search<'a> {
         let mut initial_candidates = RoaringBitmap::new();
         let mut excluded_candidates = RoaringBitmap::new();
         let mut documents_ids = Vec::new();
+        let mut timed_out = false;
+
+        loop {
+            if self.deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                if self.fail_on_timeout {
+                    return Err(UserError::SearchTimedOut.into());
+                }
+                timed_out = true;
+                break;
+            }
+
+            let FinalResult { candidates, bucket_candidates, .. } =
+                match criteria.next(&excluded_candidates)? {
+                    Some(result) => result,
+                    None => break,
+                };
 
-        while let Some(FinalResult { candidates, bucket_candidates, .. }) =
-            criteria.next(&excluded_candidates)?
-        {
             debug!("Number of candidates found {}", candidates.len());
 
             let excluded = take(&mut excluded_candidates);
@@ -226,7 +444,42 @@ impl<'a> Search<'a> {
             excluded_candidates = candidates.into_excluded();
         }
 
-        Ok(SearchResult { matching_words, candidates: initial_candidates, documents_ids })
+        let matched_fields = if self.matched_fields {
+            Some(self.documents_matched_fields(&matching_words, &documents_ids)?)
+        } else {
+            None
+        };
+
+        Ok(SearchResult {
+            matching_words,
+            candidates: initial_candidates,
+            documents_ids,
+            matched_fields,
+            timed_out,
+        })
+    }
+
+    /// Returns the fields in which any of the matching words appears, for each document,
+    /// the fields are deduced from the positions of the words in the documents.
+    fn documents_matched_fields(
+        &self,
+        matching_words: &MatchingWords,
+        documents_ids: &[DocumentId],
+    ) -> Result<HashMap<DocumentId, Vec<FieldId>>> {
+        let mut matched_fields = HashMap::with_capacity(documents_ids.len());
+        for &docid in documents_ids {
+            let mut fields = BTreeSet::new();
+            for result in self.index.docid_word_positions.prefix_iter(self.rtxn, &(docid, ""))? {
+                let ((_, word), positions) = result?;
+                if matching_words.matching_bytes(word).is_some() {
+                    for position in positions {
+                        fields.insert((position / ONE_ATTRIBUTE) as FieldId);
+                    }
+                }
+            }
+            matched_fields.insert(docid, fields.into_iter().collect());
+        }
+        Ok(matched_fields)
     }
 }
 
@@ -234,25 +487,49 @@ impl fmt::Debug for Search<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let Search {
             query,
+            query_terms,
+            match_all,
+            empty_query_matches_nothing,
             filter,
             offset,
             limit,
             sort_criteria,
             optional_words,
             authorize_typos,
+            min_word_len_one_typo,
+            min_word_len_two_typos,
             words_limit,
+            infix_matching,
+            min_word_match_ratio,
+            exact_only,
+            matched_fields,
+            language,
+            deadline,
+            fail_on_timeout,
             rtxn: _,
             index: _,
         } = self;
         f.debug_struct("Search")
             .field("query", query)
+            .field("query_terms", query_terms)
+            .field("match_all", match_all)
+            .field("empty_query_matches_nothing", empty_query_matches_nothing)
             .field("filter", filter)
             .field("offset", offset)
             .field("limit", limit)
             .field("sort_criteria", sort_criteria)
             .field("optional_words", optional_words)
             .field("authorize_typos", authorize_typos)
+            .field("min_word_len_one_typo", min_word_len_one_typo)
+            .field("min_word_len_two_typos", min_word_len_two_typos)
             .field("words_limit", words_limit)
+            .field("infix_matching", infix_matching)
+            .field("min_word_match_ratio", min_word_match_ratio)
+            .field("exact_only", exact_only)
+            .field("matched_fields", matched_fields)
+            .field("language", language)
+            .field("deadline", deadline)
+            .field("fail_on_timeout", fail_on_timeout)
             .finish()
     }
 }
@@ -263,6 +540,25 @@ pub struct SearchResult {
     pub candidates: RoaringBitmap,
     // TODO those documents ids should be associated with their criteria scores.
     pub documents_ids: Vec<DocumentId>,
+    /// The fields in which a query word matched for each returned document,
+    /// only computed when asked with [`Search::matched_fields`].
+    pub matched_fields: Option<HashMap<DocumentId, Vec<FieldId>>>,
+    /// Whether the search deadline was exceeded, the documents are then only a partial result.
+    pub timed_out: bool,
+}
+
+/// A search result that owns all of its data, the documents are
+/// already retrieved from the index and converted into JSON.
+#[derive(Default)]
+pub struct OwnedSearchResult {
+    pub matching_words: MatchingWords,
+    pub candidates: RoaringBitmap,
+    pub documents: Vec<(DocumentId, Value)>,
+    /// The fields in which a query word matched for each returned document,
+    /// only computed when asked with [`Search::matched_fields`].
+    pub matched_fields: Option<HashMap<DocumentId, Vec<FieldId>>>,
+    /// Whether the search deadline was exceeded, the documents are then only a partial result.
+    pub timed_out: bool,
 }
 
 pub type WordDerivationsCache = HashMap<(String, bool, u8), Vec<(String, u8)>>;
@@ -305,3 +601,393 @@ pub fn build_dfa(word: &str, typos: u8, is_prefix: bool) -> DFA {
         lev.build_dfa(word)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::index::tests::TempIndex;
+    use crate::update::{IndexDocuments, Settings, UpdateFormat};
+
+    /// Creates a temporary index with the given settings and indexes the documents into it.
+    fn index_documents<F>(content: &[u8], settings: F) -> TempIndex
+    where
+        F: FnOnce(&mut Settings),
+    {
+        let index = TempIndex::new();
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        settings(&mut builder);
+        builder.execute(|_, _| ()).unwrap();
+
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+        index
+    }
+
+    fn assert_send_static<T: Send + 'static>() {}
+
+    #[test]
+    fn owned_search_result_is_send() {
+        assert_send_static::<OwnedSearchResult>();
+    }
+
+    #[test]
+    fn execute_owned_matches_execute() {
+        let content = &br#"[
+            { "id": 1, "name": "kevin" },
+            { "id": 2, "name": "kevina" },
+            { "id": 3, "name": "benoit" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("kevin");
+
+        let SearchResult { candidates, documents_ids, .. } = search.execute().unwrap();
+        let owned = search.execute_owned().unwrap();
+        drop(rtxn);
+
+        assert_eq!(owned.candidates, candidates);
+        let owned_ids: Vec<_> = owned.documents.iter().map(|(id, _)| *id).collect();
+        assert_eq!(owned_ids, documents_ids);
+
+        let names: Vec<_> = owned.documents.iter().map(|(_, doc)| doc["name"].clone()).collect();
+        assert!(names.contains(&Value::from("kevin")));
+        assert!(names.contains(&Value::from("kevina")));
+    }
+
+    #[test]
+    fn query_terms_matches_query_string() {
+        let content = &br#"[
+            { "id": 1, "text": "the best pizza of new york" },
+            { "id": 2, "text": "york has a new pizza place" },
+            { "id": 3, "text": "new york is big" },
+            { "id": 4, "text": "pizzas of new york city" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+        let expected = index.search(&rtxn).query("\"new york\" pizza").execute().unwrap();
+
+        let terms = vec![
+            QueryTerm::Phrase(vec!["new".to_string(), "york".to_string()]),
+            QueryTerm::Word("pizza".to_string()),
+        ];
+        let result = index.search(&rtxn).query_terms(terms).execute().unwrap();
+
+        assert_eq!(result.candidates, expected.candidates);
+        assert_eq!(result.documents_ids, expected.documents_ids);
+        assert!(!result.documents_ids.contains(&1));
+    }
+
+    #[test]
+    fn phrase_requires_consecutive_words() {
+        let content = &br#"[
+            { "id": 1, "text": "a cheap hotel in new york" },
+            { "id": 2, "text": "a cheap york hotel, not new" },
+            { "id": 3, "text": "a cheap hotel in york new" },
+            { "id": 4, "text": "new york hotel" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+        let external_ids = index.external_documents_ids(&rtxn).unwrap();
+        let mut search = index.search(&rtxn);
+        search.optional_words(false);
+
+        let result = search.query("cheap \"new york\" hotel").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![external_ids.get("1").unwrap()]);
+
+        // Without the quotes the words are not required to be consecutive.
+        let result = search.query("cheap new york hotel").execute().unwrap();
+        assert_eq!(result.candidates.len(), 3);
+    }
+
+    #[test]
+    fn min_word_len_typos() {
+        let content = &br#"[
+            { "id": 1, "code": "abcd" },
+            { "id": 2, "code": "catalogue" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+
+        // By default the four bytes words must match exactly.
+        let result = index.search(&rtxn).query("abce").execute().unwrap();
+        assert!(result.documents_ids.is_empty());
+
+        let result = index.search(&rtxn).query("abce").min_word_len_one_typo(4).execute().unwrap();
+        assert_eq!(result.documents_ids.len(), 1);
+
+        let mut search = index.search(&rtxn);
+        search.query("abce").min_word_len_one_typo(4).authorize_typos(false);
+        assert!(search.execute().unwrap().documents_ids.is_empty());
+
+        // By default the nine bytes words can match with two typos.
+        let result = index.search(&rtxn).query("cataloxxe").execute().unwrap();
+        assert_eq!(result.documents_ids.len(), 1);
+
+        let result =
+            index.search(&rtxn).query("cataloxxe").min_word_len_two_typos(10).execute().unwrap();
+        assert!(result.documents_ids.is_empty());
+    }
+
+    #[test]
+    fn match_all() {
+        let content = &br#"[
+            { "id": 1, "name": "kevin", "age": 20 },
+            { "id": 2, "name": "kevina", "age": 43 },
+            { "id": 3, "name": "benoit", "age": 34 }
+        ]"#[..];
+        let index = index_documents(content, |builder| {
+            builder.set_sortable_fields(maplit::hashset! { "age".to_string() });
+        });
+
+        let rtxn = index.read_txn().unwrap();
+        let age_desc = vec![AscDesc::Desc("age".to_string())];
+
+        // The query is ignored and the documents are ordered by the sort.
+        let mut search = index.search(&rtxn);
+        search.query("kevin").match_all().sort_criteria(age_desc.clone());
+        let result = search.execute().unwrap();
+        assert_eq!(result.documents_ids, vec![1, 2, 0]);
+        assert_eq!(result.candidates.len(), 3);
+
+        // A text query only returns the matching documents.
+        let mut search = index.search(&rtxn);
+        search.query("kevin").sort_criteria(age_desc);
+        let result = search.execute().unwrap();
+        assert!(!result.documents_ids.contains(&2));
+
+        // An empty query string is a placeholder search returning all the documents.
+        let result = index.search(&rtxn).query("").execute().unwrap();
+        assert_eq!(result.candidates.len(), 3);
+
+        // Unless it is explicitly asked to match nothing.
+        let mut search = index.search(&rtxn);
+        search.query("").empty_query_matches_nothing(true);
+        assert!(search.execute().unwrap().documents_ids.is_empty());
+        search.match_all();
+        assert_eq!(search.execute().unwrap().candidates.len(), 3);
+    }
+
+    #[test]
+    fn infix_matching() {
+        let content = &br#"[
+            { "id": 1, "city": "newyork" },
+            { "id": 2, "city": "paris" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+
+        let result = index.search(&rtxn).query("york").execute().unwrap();
+        assert!(result.documents_ids.is_empty());
+
+        let result = index.search(&rtxn).query("york").infix_matching(true).execute().unwrap();
+        assert_eq!(result.documents_ids, vec![0]);
+    }
+
+    #[test]
+    fn matched_fields() {
+        let content = &br#"[
+            { "id": 1, "title": "hello world", "tags": "rust" },
+            { "id": 2, "title": "rust is great", "tags": "rust" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let title = fields_ids_map.id("title").unwrap();
+        let tags = fields_ids_map.id("tags").unwrap();
+
+        let result = index.search(&rtxn).query("rust").execute().unwrap();
+        assert!(result.matched_fields.is_none());
+
+        let result = index.search(&rtxn).query("rust").matched_fields(true).execute().unwrap();
+        let matched_fields = result.matched_fields.unwrap();
+        assert_eq!(matched_fields.len(), 2);
+        assert_eq!(matched_fields[&0], vec![tags]);
+        let mut expected = vec![title, tags];
+        expected.sort_unstable();
+        assert_eq!(matched_fields[&1], expected);
+    }
+
+    #[test]
+    fn min_word_match_ratio() {
+        let content = &br#"[
+            { "id": 1, "text": "alpha bravo charlie delta" },
+            { "id": 2, "text": "alpha bravo charlie" },
+            { "id": 3, "text": "alpha bravo" },
+            { "id": 4, "text": "alpha" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+        let query = "alpha bravo charlie delta";
+
+        let result = index.search(&rtxn).query(query).execute().unwrap();
+        let mut documents_ids = result.documents_ids;
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![0, 1, 2, 3]);
+
+        // At least 3 of the 4 query words must match.
+        let result = index.search(&rtxn).query(query).min_word_match_ratio(0.75).execute().unwrap();
+        let mut documents_ids = result.documents_ids;
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![0, 1]);
+    }
+
+    #[test]
+    fn exact_only() {
+        let content = &br#"[
+            { "id": 1, "sku": "ABC123" },
+            { "id": 2, "sku": "XYZ789" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+
+        // One typo is tolerated by default.
+        let result = index.search(&rtxn).query("ABC124").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![0]);
+
+        let result = index.search(&rtxn).query("ABC124").exact_only(true).execute().unwrap();
+        assert!(result.documents_ids.is_empty());
+
+        let result = index.search(&rtxn).query("ABC123").exact_only(true).execute().unwrap();
+        assert_eq!(result.documents_ids, vec![0]);
+
+        // A prefix of the identifier does not match either.
+        let result = index.search(&rtxn).query("ABC12").exact_only(true).execute().unwrap();
+        assert!(result.documents_ids.is_empty());
+    }
+
+    #[test]
+    fn exactness_prefix_tier() {
+        let content = &br#"[
+            { "id": 1, "text": "we say hallo to them" },
+            { "id": 2, "text": "we say hellos to them" },
+            { "id": 3, "text": "we say hello to them" }
+        ]"#[..];
+        let index = index_documents(content, |builder| {
+            builder.set_criteria(vec!["words".to_string(), "exactness".to_string()]);
+        });
+
+        // The prefix and the typo matches are not distinguished by default.
+        let rtxn = index.read_txn().unwrap();
+        let result = index.search(&rtxn).query("hello").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![2, 0, 1]);
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 1);
+        builder.set_exactness_prefix_tier(true);
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // The exact word comes first, then the prefix and finally the typo.
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.exactness_prefix_tier(&rtxn).unwrap());
+        let result = index.search(&rtxn).query("hello").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn estimate_candidates() {
+        let content = &br#"[
+            { "id": 1, "text": "the best pizza of new york", "year": 2019 },
+            { "id": 2, "text": "york has a new pizza place", "year": 2020 },
+            { "id": 3, "text": "new york is big", "year": 2021 },
+            { "id": 4, "text": "pizzas of new york city", "year": 2021 },
+            { "id": 5, "text": "a burger in paris", "year": 2021 }
+        ]"#[..];
+        let index = index_documents(content, |builder| {
+            builder.set_filterable_fields(maplit::hashset! { "year".to_string() });
+        });
+
+        let rtxn = index.read_txn().unwrap();
+        let filter = FilterCondition::from_str(&rtxn, &index, "year = 2021").unwrap();
+        for query in &["pizza", "new york", "burger", "paris pizza", "unknown"] {
+            let mut search = index.search(&rtxn);
+            search.query(*query);
+            let estimate = search.estimate_candidates().unwrap();
+            let result = search.execute().unwrap();
+            assert_eq!(estimate, result.candidates.len(), "{}", query);
+
+            search.filter(filter.clone());
+            let estimate = search.estimate_candidates().unwrap();
+            let result = search.execute().unwrap();
+            assert_eq!(estimate, result.candidates.len(), "{} with a filter", query);
+        }
+
+        // Without any query all the documents are candidates.
+        assert_eq!(index.search(&rtxn).estimate_candidates().unwrap(), 5);
+        assert_eq!(index.search(&rtxn).query("  ").estimate_candidates().unwrap(), 5);
+        let mut search = index.search(&rtxn);
+        search.query("  ").empty_query_matches_nothing(true);
+        assert_eq!(search.estimate_candidates().unwrap(), 0);
+    }
+
+    #[test]
+    fn deadline() {
+        let content = &br#"[
+            { "id": 1, "name": "kevin" },
+            { "id": 2, "name": "kevina" },
+            { "id": 3, "name": "benoit" }
+        ]"#[..];
+        let index = index_documents(content, |_| ());
+
+        let rtxn = index.read_txn().unwrap();
+
+        let mut search = index.search(&rtxn);
+        search.query("kevin").deadline(Instant::now());
+        let result = search.execute().unwrap();
+        assert!(result.timed_out);
+        assert!(result.documents_ids.is_empty());
+
+        search.fail_on_timeout(true);
+        match search.execute() {
+            Err(crate::Error::UserError(UserError::SearchTimedOut)) => (),
+            Err(error) => panic!("expected a timed out error, got {}", error),
+            Ok(_) => panic!("expected a timed out error"),
+        }
+
+        // A generous deadline does not change the results.
+        let mut search = index.search(&rtxn);
+        search.query("kevin").deadline(Instant::now() + std::time::Duration::from_secs(60));
+        let result = search.execute().unwrap();
+        assert!(!result.timed_out);
+        assert_eq!(result.documents_ids.len(), 2);
+    }
+
+    #[test]
+    fn ranking_score() {
+        let content = &br#"[
+            { "id": 1, "name": "kevin", "_rankingScore": 1.5 },
+            { "id": 2, "name": "kevin", "_rankingScore": 8 },
+            { "id": 3, "name": "kevin", "_rankingScore": 4 },
+            { "id": 4, "name": "benoit", "_rankingScore": 10 }
+        ]"#[..];
+        let index = index_documents(content, |builder| {
+            builder.set_searchable_fields(vec!["name".to_string(), "_rankingScore".to_string()]);
+            builder.set_criteria(vec!["words".to_string(), "rankingscore".to_string()]);
+            builder.set_displayed_fields(vec!["name".to_string(), "_rankingScore".to_string()]);
+        });
+
+        let rtxn = index.read_txn().unwrap();
+        let result = index.search(&rtxn).query("kevin").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![1, 2, 0]);
+
+        // The ranking score is not a filterable field.
+        FilterCondition::from_str(&rtxn, &index, "_rankingScore > 2").unwrap_err();
+
+        // The ranking score is neither displayed nor searchable.
+        assert_eq!(index.displayed_fields(&rtxn).unwrap(), Some(vec!["name"]));
+        assert_eq!(index.searchable_fields(&rtxn).unwrap(), Some(vec!["name"]));
+    }
+}
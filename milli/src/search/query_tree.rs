@@ -1,6 +1,6 @@
 use std::{cmp, fmt, mem};
 
-use fst::Set;
+use fst::{Automaton, IntoStreamer, Set, Streamer};
 use meilisearch_tokenizer::token::SeparatorKind;
 use meilisearch_tokenizer::tokenizer::TokenStream;
 use meilisearch_tokenizer::TokenKind;
@@ -12,6 +12,12 @@ use crate::{Index, Result};
 type IsOptionalWord = bool;
 type IsPrefix = bool;
 
+/// The minimum number of bytes of the query words that can match with one typo.
+const DEFAULT_MIN_WORD_LEN_ONE_TYPO: u8 = 5;
+
+/// The minimum number of bytes of the query words that can match with two typos.
+const DEFAULT_MIN_WORD_LEN_TWO_TYPOS: u8 = 9;
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Operation {
     And(Vec<Operation>),
@@ -149,6 +155,10 @@ impl fmt::Debug for Query {
 trait Context {
     fn word_docids(&self, word: &str) -> heed::Result<Option<RoaringBitmap>>;
     fn synonyms<S: AsRef<str>>(&self, words: &[S]) -> heed::Result<Option<Vec<Vec<String>>>>;
+    /// Returns the words of the dictionary containing the given word, the word itself excluded.
+    fn infix_words(&self, _word: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
     fn word_documents_count(&self, word: &str) -> heed::Result<Option<u64>> {
         match self.word_docids(word)? {
             Some(rb) => Ok(Some(rb.len())),
@@ -162,8 +172,12 @@ pub struct QueryTreeBuilder<'a> {
     rtxn: &'a heed::RoTxn<'a>,
     index: &'a Index,
     optional_words: bool,
-    authorize_typos: bool,
+    typo_config: TypoConfig,
     words_limit: Option<usize>,
+    language: Option<String>,
+    infix_matching: bool,
+    min_word_match_ratio: Option<f32>,
+    exact_only: bool,
 }
 
 impl<'a> Context for QueryTreeBuilder<'a> {
@@ -175,6 +189,24 @@ impl<'a> Context for QueryTreeBuilder<'a> {
         self.index.words_synonyms(self.rtxn, words)
     }
 
+    fn infix_words(&self, word: &str) -> Result<Vec<String>> {
+        if !self.infix_matching || word.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let fst = self.index.words_fst(self.rtxn)?;
+        let mut stream = fst.search(Substring::new(word)).into_stream();
+        let mut words = Vec::new();
+        while let Some(bytes) = stream.next() {
+            let found = std::str::from_utf8(bytes)?;
+            if found != word {
+                words.push(found.to_string());
+            }
+        }
+
+        Ok(words)
+    }
+
     fn word_documents_count(&self, word: &str) -> heed::Result<Option<u64>> {
         self.index.word_documents_count(self.rtxn, word)
     }
@@ -184,7 +216,17 @@ impl<'a> QueryTreeBuilder<'a> {
     /// Create a `QueryTreeBuilder` from a heed ReadOnly transaction `rtxn`
     /// and an Index `index`.
     pub fn new(rtxn: &'a heed::RoTxn<'a>, index: &'a Index) -> Self {
-        Self { rtxn, index, optional_words: true, authorize_typos: true, words_limit: None }
+        Self {
+            rtxn,
+            index,
+            optional_words: true,
+            typo_config: TypoConfig::default(),
+            words_limit: None,
+            language: None,
+            infix_matching: false,
+            min_word_match_ratio: None,
+            exact_only: false,
+        }
     }
 
     /// if `optional_words` is set to `false` the query tree will be
@@ -203,7 +245,39 @@ impl<'a> QueryTreeBuilder<'a> {
     /// default value if not called: `true`
     #[allow(unused)]
     pub fn authorize_typos(&mut self, authorize_typos: bool) -> &mut Self {
-        self.authorize_typos = authorize_typos;
+        self.typo_config.authorize_typos = authorize_typos;
+        self
+    }
+
+    /// The query words with at least this number of bytes can match with one typo.
+    /// default value if not called: `5`
+    pub fn min_word_len_one_typo(&mut self, min_len: u8) -> &mut Self {
+        self.typo_config.min_word_len_one_typo = min_len;
+        self
+    }
+
+    /// The query words with at least this number of bytes can match with two typos.
+    /// default value if not called: `9`
+    pub fn min_word_len_two_typos(&mut self, min_len: u8) -> &mut Self {
+        self.typo_config.min_word_len_two_typos = min_len;
+        self
+    }
+
+    /// When `optional_words` is `true`, at least this ratio of the query words
+    /// must be matched, the others can be dropped, the phrases are always kept.
+    /// default value if not called: any number of words can be dropped
+    pub fn min_word_match_ratio(&mut self, ratio: f32) -> &mut Self {
+        self.min_word_match_ratio = Some(ratio);
+        self
+    }
+
+    /// if `exact_only` is set to `true` the query tree will only contain the query words
+    /// and phrases as they are, all of them must be matched without any typo, prefix,
+    /// synonym or split/concatenation of the words (`optional_words`, `authorize_typos`
+    /// and `infix_matching` will be ignored).
+    /// default value if not called: `false`
+    pub fn exact_only(&mut self, exact_only: bool) -> &mut Self {
+        self.exact_only = exact_only;
         self
     }
 
@@ -214,6 +288,23 @@ impl<'a> QueryTreeBuilder<'a> {
         self
     }
 
+    /// Use the stop words registered for this language instead of the global ones.
+    pub fn language(&mut self, language: impl Into<String>) -> &mut Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// if `infix_matching` is set to `true` the single words of the query also match
+    /// the words of the dictionary that contain them, e.g. `york` matches `newyork`.
+    ///
+    /// This scans the whole words FST for every query word and can add many words
+    /// to the query tree, it should only be enabled on indexes with a small vocabulary.
+    /// default value if not called: `false`
+    pub fn infix_matching(&mut self, infix_matching: bool) -> &mut Self {
+        self.infix_matching = infix_matching;
+        self
+    }
+
     /// Build the query tree:
     /// - if `optional_words` is set to `false` the query tree will be
     ///   generated forcing all query words to be present in each matching documents
@@ -222,15 +313,48 @@ impl<'a> QueryTreeBuilder<'a> {
     ///   forcing all query words to match documents without any typo
     ///   (the criterion `typo` will be ignored)
     pub fn build(&self, query: TokenStream) -> Result<Option<(Operation, PrimitiveQuery)>> {
-        let stop_words = self.index.stop_words(self.rtxn)?;
+        let stop_words = self.index.stop_words_for_language(self.rtxn, self.language.as_deref())?;
         let primitive_query = create_primitive_query(query, stop_words, self.words_limit);
         if !primitive_query.is_empty() {
-            let qt = create_query_tree(
-                self,
-                self.optional_words,
-                self.authorize_typos,
-                &primitive_query,
-            )?;
+            let qt = if self.exact_only {
+                create_exact_query_tree(&primitive_query)
+            } else {
+                create_query_tree(
+                    self,
+                    self.optional_words,
+                    self.typo_config,
+                    self.min_word_match_ratio,
+                    &primitive_query,
+                )?
+            };
+            Ok(Some((qt, primitive_query)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Build the query tree from an already split list of terms, bypassing the tokenization,
+    /// the same rules than for `build` apply, only the last word is considered as a prefix
+    /// and the other words that are stop words of the language are ignored.
+    pub fn build_from_terms(
+        &self,
+        terms: &[QueryTerm],
+    ) -> Result<Option<(Operation, PrimitiveQuery)>> {
+        let stop_words = self.index.stop_words_for_language(self.rtxn, self.language.as_deref())?;
+        let primitive_query =
+            create_primitive_query_from_terms(terms, stop_words, self.words_limit);
+        if !primitive_query.is_empty() {
+            let qt = if self.exact_only {
+                create_exact_query_tree(&primitive_query)
+            } else {
+                create_query_tree(
+                    self,
+                    self.optional_words,
+                    self.typo_config,
+                    self.min_word_match_ratio,
+                    &primitive_query,
+                )?
+            };
             Ok(Some((qt, primitive_query)))
         } else {
             Ok(None)
@@ -258,17 +382,91 @@ fn split_best_frequency(ctx: &impl Context, word: &str) -> heed::Result<Option<O
     Ok(best.map(|(_, left, right)| Operation::Phrase(vec![left.to_string(), right.to_string()])))
 }
 
-/// Return the `QueryKind` of a word depending on `authorize_typos`
-/// and the provided word length.
-fn typos(word: String, authorize_typos: bool) -> QueryKind {
-    if authorize_typos {
-        match word.len() {
-            0..=4 => QueryKind::exact(word),
-            5..=8 => QueryKind::tolerant(1, word),
-            _ => QueryKind::tolerant(2, word),
+/// An automaton matching the keys that contain the given bytes,
+/// the state is the length of the longest matched prefix of the pattern.
+struct Substring<'a> {
+    pattern: &'a [u8],
+    failure: Vec<usize>,
+}
+
+impl<'a> Substring<'a> {
+    fn new(pattern: &'a str) -> Substring<'a> {
+        let pattern = pattern.as_bytes();
+        let mut failure = vec![0; pattern.len()];
+        let mut len = 0;
+        for i in 1..pattern.len() {
+            while len > 0 && pattern[i] != pattern[len] {
+                len = failure[len - 1];
+            }
+            if pattern[i] == pattern[len] {
+                len += 1;
+            }
+            failure[i] = len;
         }
-    } else {
+        Substring { pattern, failure }
+    }
+}
+
+impl Automaton for Substring<'_> {
+    type State = usize;
+
+    fn start(&self) -> usize {
+        0
+    }
+
+    fn is_match(&self, state: &usize) -> bool {
+        *state == self.pattern.len()
+    }
+
+    fn will_always_match(&self, state: &usize) -> bool {
+        self.is_match(state)
+    }
+
+    fn accept(&self, state: &usize, byte: u8) -> usize {
+        let mut state = *state;
+        if state == self.pattern.len() {
+            return state;
+        }
+        loop {
+            if self.pattern[state] == byte {
+                return state + 1;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.failure[state - 1];
+        }
+    }
+}
+
+/// How many typos the query words can contain, depending on their length.
+#[derive(Debug, Clone, Copy)]
+struct TypoConfig {
+    authorize_typos: bool,
+    min_word_len_one_typo: u8,
+    min_word_len_two_typos: u8,
+}
+
+impl Default for TypoConfig {
+    fn default() -> TypoConfig {
+        TypoConfig {
+            authorize_typos: true,
+            min_word_len_one_typo: DEFAULT_MIN_WORD_LEN_ONE_TYPO,
+            min_word_len_two_typos: DEFAULT_MIN_WORD_LEN_TWO_TYPOS,
+        }
+    }
+}
+
+/// Return the `QueryKind` of a word depending on the `TypoConfig`
+/// and the provided word length.
+fn typos(word: String, config: TypoConfig) -> QueryKind {
+    let len = word.len();
+    if !config.authorize_typos || len < config.min_word_len_one_typo as usize {
         QueryKind::exact(word)
+    } else if len < config.min_word_len_two_typos as usize {
+        QueryKind::tolerant(1, word)
+    } else {
+        QueryKind::tolerant(2, word)
     }
 }
 
@@ -293,31 +491,52 @@ fn synonyms(ctx: &impl Context, word: &[&str]) -> heed::Result<Option<Vec<Operat
     }))
 }
 
+/// Creates a query tree requiring all the words and phrases of the
+/// primitive query to be matched exactly, without any derivation.
+fn create_exact_query_tree(query: &[PrimitiveQueryPart]) -> Operation {
+    let children = query
+        .iter()
+        .map(|part| match part {
+            PrimitiveQueryPart::Word(word, _prefix) => {
+                Operation::Query(Query { prefix: false, kind: QueryKind::exact(word.clone()) })
+            }
+            PrimitiveQueryPart::Phrase(words) => Operation::phrase(words.clone()),
+        })
+        .collect();
+
+    Operation::and(children)
+}
+
 /// Main function that creates the final query tree from the primitive query.
 fn create_query_tree(
     ctx: &impl Context,
     optional_words: bool,
-    authorize_typos: bool,
+    typo_config: TypoConfig,
+    min_word_match_ratio: Option<f32>,
     query: &[PrimitiveQueryPart],
 ) -> Result<Operation> {
     /// Matches on the `PrimitiveQueryPart` and create an operation from it.
     fn resolve_primitive_part(
         ctx: &impl Context,
-        authorize_typos: bool,
+        typo_config: TypoConfig,
         part: PrimitiveQueryPart,
     ) -> Result<Operation> {
         match part {
             // 1. try to split word in 2
             // 2. try to fetch synonyms
-            // 3. create an operation containing the word
-            // 4. wrap all in an OR operation
+            // 3. fetch the words containing it if infix matching is enabled
+            // 4. create an operation containing the word
+            // 5. wrap all in an OR operation
             PrimitiveQueryPart::Word(word, prefix) => {
                 let mut children = synonyms(ctx, &[&word])?.unwrap_or_default();
                 if let Some(child) = split_best_frequency(ctx, &word)? {
                     children.push(child);
                 }
-                children
-                    .push(Operation::Query(Query { prefix, kind: typos(word, authorize_typos) }));
+                for infix in ctx.infix_words(&word)? {
+                    let kind = QueryKind::exact(infix);
+                    children.push(Operation::Query(Query { prefix: false, kind }));
+                }
+                children.push(Operation::Query(Query { prefix, kind: typos(word, typo_config) }));
                 Ok(Operation::or(false, children))
             }
             // create a CONSECUTIVE operation wrapping all word in the phrase
@@ -328,7 +547,7 @@ fn create_query_tree(
     /// Create all ngrams 1..=3 generating query tree branches.
     fn ngrams(
         ctx: &impl Context,
-        authorize_typos: bool,
+        typo_config: TypoConfig,
         query: &[PrimitiveQueryPart],
     ) -> Result<Operation> {
         const MAX_NGRAM: usize = 3;
@@ -345,8 +564,7 @@ fn create_query_tree(
 
                     match group {
                         [part] => {
-                            let operation =
-                                resolve_primitive_part(ctx, authorize_typos, part.clone())?;
+                            let operation = resolve_primitive_part(ctx, typo_config, part.clone())?;
                             and_op_children.push(operation);
                         }
                         words => {
@@ -364,14 +582,14 @@ fn create_query_tree(
                             let mut operations = synonyms(ctx, &words)?.unwrap_or_default();
                             let concat = words.concat();
                             let query =
-                                Query { prefix: is_prefix, kind: typos(concat, authorize_typos) };
+                                Query { prefix: is_prefix, kind: typos(concat, typo_config) };
                             operations.push(Operation::Query(query));
                             and_op_children.push(Operation::or(false, operations));
                         }
                     }
 
                     if !is_last {
-                        let ngrams = ngrams(ctx, authorize_typos, tail)?;
+                        let ngrams = ngrams(ctx, typo_config, tail)?;
                         and_op_children.push(ngrams);
                     }
                     or_op_children.push(Operation::and(and_op_children));
@@ -386,13 +604,22 @@ fn create_query_tree(
     /// Create a new branch removing the last non-phrase query parts.
     fn optional_word(
         ctx: &impl Context,
-        authorize_typos: bool,
+        typo_config: TypoConfig,
+        min_word_match_ratio: Option<f32>,
         query: PrimitiveQuery,
     ) -> Result<Operation> {
         let number_phrases = query.iter().filter(|p| p.is_phrase()).count();
+        let number_words = query.len() - number_phrases;
         let mut operation_children = Vec::new();
 
-        let start = number_phrases + (number_phrases == 0) as usize;
+        let min_words = match min_word_match_ratio {
+            Some(ratio) => {
+                let ratio = ratio.max(0.0).min(1.0);
+                (number_words as f32 * ratio).ceil() as usize
+            }
+            None => 0,
+        };
+        let start = number_phrases + cmp::max(min_words, (number_phrases == 0) as usize);
         for len in start..=query.len() {
             let mut word_count = len - number_phrases;
             let query: Vec<_> = query
@@ -410,7 +637,7 @@ fn create_query_tree(
                 .cloned()
                 .collect();
 
-            let ngrams = ngrams(ctx, authorize_typos, &query)?;
+            let ngrams = ngrams(ctx, typo_config, &query)?;
             operation_children.push(ngrams);
         }
 
@@ -418,9 +645,9 @@ fn create_query_tree(
     }
 
     if optional_words {
-        optional_word(ctx, authorize_typos, query.to_vec())
+        optional_word(ctx, typo_config, min_word_match_ratio, query.to_vec())
     } else {
-        ngrams(ctx, authorize_typos, query)
+        ngrams(ctx, typo_config, query)
     }
 }
 
@@ -442,6 +669,39 @@ impl PrimitiveQueryPart {
     }
 }
 
+/// A part of a query that is given already split, a word or a phrase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryTerm {
+    Word(String),
+    Phrase(Vec<String>),
+}
+
+/// Create primitive query from a list of already split terms,
+/// only the last word of the query is considered as a prefix.
+fn create_primitive_query_from_terms(
+    terms: &[QueryTerm],
+    stop_words: Option<Set<&[u8]>>,
+    words_limit: Option<usize>,
+) -> PrimitiveQuery {
+    let parts_limit = words_limit.unwrap_or(usize::MAX);
+    let last_index = terms.len().saturating_sub(1);
+    let is_stop_word =
+        |word: &str| stop_words.as_ref().map_or(false, |swords| swords.contains(word));
+
+    terms
+        .iter()
+        .enumerate()
+        .filter_map(|(i, term)| match term {
+            QueryTerm::Word(word) if word.is_empty() => None,
+            QueryTerm::Word(word) if i != last_index && is_stop_word(word) => None,
+            QueryTerm::Word(word) => Some(PrimitiveQueryPart::Word(word.clone(), i == last_index)),
+            QueryTerm::Phrase(words) if words.is_empty() => None,
+            QueryTerm::Phrase(words) => Some(PrimitiveQueryPart::Phrase(words.clone())),
+        })
+        .take(parts_limit)
+        .collect()
+}
+
 /// Create primitive query from tokenized query string,
 /// the primitive query is an intermediate state to build the query tree.
 fn create_primitive_query(
@@ -556,8 +816,9 @@ mod test {
         ) -> Result<Option<(Operation, PrimitiveQuery)>> {
             let primitive_query = create_primitive_query(query, None, words_limit);
             if !primitive_query.is_empty() {
+                let typo_config = TypoConfig { authorize_typos, ..TypoConfig::default() };
                 let qt =
-                    create_query_tree(self, optional_words, authorize_typos, &primitive_query)?;
+                    create_query_tree(self, optional_words, typo_config, None, &primitive_query)?;
                 Ok(Some((qt, primitive_query)))
             } else {
                 Ok(None)
@@ -1192,4 +1453,69 @@ mod test {
 
         assert_eq!(expected, query_tree);
     }
+
+    #[test]
+    fn phrase_and_words_from_terms() {
+        let query = "\"hey friends\" good morning";
+        let analyzer = Analyzer::new(AnalyzerConfig::<Vec<u8>>::default());
+        let result = analyzer.analyze(query);
+        let tokens = result.tokens();
+
+        let context = TestContext::default();
+        let (expected, _) = context.build(false, true, None, tokens).unwrap().unwrap();
+
+        let terms = vec![
+            QueryTerm::Phrase(vec!["hey".to_string(), "friends".to_string()]),
+            QueryTerm::Word("good".to_string()),
+            QueryTerm::Word("morning".to_string()),
+        ];
+        let primitive_query = create_primitive_query_from_terms(&terms, None, None);
+        let query_tree =
+            create_query_tree(&context, false, TypoConfig::default(), None, &primitive_query)
+                .unwrap();
+
+        assert_eq!(expected, query_tree);
+    }
+
+    #[test]
+    fn stop_words_from_terms() {
+        let stop_words = Set::from_iter(&["the"]).unwrap();
+        let stop_words = Set::new(stop_words.as_fst().as_bytes()).unwrap();
+
+        let terms = vec![
+            QueryTerm::Word("the".to_string()),
+            QueryTerm::Phrase(vec!["the".to_string(), "end".to_string()]),
+            QueryTerm::Word("the".to_string()),
+        ];
+        let primitive_query = create_primitive_query_from_terms(&terms, Some(stop_words), None);
+
+        // Only the last word can be a stop word, the phrases are kept as is.
+        assert_eq!(primitive_query.len(), 2);
+        assert!(matches!(
+            &primitive_query[0],
+            PrimitiveQueryPart::Phrase(words) if words == &["the", "end"]
+        ));
+        assert!(
+            matches!(&primitive_query[1], PrimitiveQueryPart::Word(word, true) if word == "the")
+        );
+    }
+
+    #[test]
+    fn typos_depend_on_the_word_length() {
+        let config = TypoConfig::default();
+        assert_eq!(typos("abcd".to_string(), config), QueryKind::exact("abcd".to_string()));
+        assert_eq!(typos("abcde".to_string(), config), QueryKind::tolerant(1, "abcde".to_string()));
+        assert_eq!(
+            typos("abcdefghi".to_string(), config),
+            QueryKind::tolerant(2, "abcdefghi".to_string())
+        );
+
+        let config = TypoConfig { min_word_len_one_typo: 2, min_word_len_two_typos: 4, ..config };
+        assert_eq!(typos("a".to_string(), config), QueryKind::exact("a".to_string()));
+        assert_eq!(typos("ab".to_string(), config), QueryKind::tolerant(1, "ab".to_string()));
+        assert_eq!(typos("abcd".to_string(), config), QueryKind::tolerant(2, "abcd".to_string()));
+
+        let config = TypoConfig { authorize_typos: false, ..config };
+        assert_eq!(typos("abcd".to_string(), config), QueryKind::exact("abcd".to_string()));
+    }
 }
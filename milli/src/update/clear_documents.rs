@@ -56,6 +56,11 @@ impl<'t, 'u, 'i> ClearDocuments<'t, 'u, 'i> {
             self.index.put_string_faceted_documents_ids(self.wtxn, field_id, &empty)?;
         }
 
+        // We clean the documents ids of the languages.
+        for language in self.index.languages_documents_ids(self.wtxn)?.keys() {
+            self.index.put_language_documents_ids(self.wtxn, language, &empty)?;
+        }
+
         // Clear the other databases.
         word_docids.clear(self.wtxn)?;
         word_prefix_docids.clear(self.wtxn)?;
@@ -6,6 +6,7 @@ use fst::IntoStreamer;
 use heed::types::ByteSlice;
 use heed::{BytesDecode, BytesEncode};
 use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::ClearDocuments;
@@ -15,13 +16,25 @@ use crate::heed_codec::facet::{
 };
 use crate::heed_codec::CboRoaringBitmapCodec;
 use crate::index::{db_name, main_key};
-use crate::{DocumentId, ExternalDocumentsIds, FieldId, Index, Result, SmallString32, BEU32};
+use crate::{
+    DocumentId, ExternalDocumentsIds, FieldId, FilterCondition, Index, PrimaryKeyPolicy, Result,
+    SmallString32, BEU32,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentDeletionResult {
+    /// The number of documents deleted by this update.
+    pub deleted_documents: u64,
+    /// The external ids of the deleted documents that were in the index, sorted.
+    pub external_ids: Vec<String>,
+}
 
 pub struct DeleteDocuments<'t, 'u, 'i> {
     wtxn: &'t mut heed::RwTxn<'i, 'u>,
     index: &'i Index,
     external_documents_ids: ExternalDocumentsIds<'static>,
     documents_ids: RoaringBitmap,
+    strict_field_distribution: bool,
     update_id: u64,
 }
 
@@ -38,6 +51,7 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
             index,
             external_documents_ids,
             documents_ids: RoaringBitmap::new(),
+            strict_field_distribution: false,
             update_id,
         })
     }
@@ -56,14 +70,35 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
         Some(docid)
     }
 
+    /// Deletes the documents matching the filter, it is evaluated right away,
+    /// on the documents of the index as seen by this write transaction.
+    pub fn delete_by_filter(&mut self, condition: FilterCondition) -> Result<()> {
+        let docids = condition.evaluate(self.wtxn, self.index)?;
+        self.delete_documents(&docids);
+        Ok(())
+    }
+
+    /// Returns an error when more field occurrences are removed than the field distribution
+    /// counts, instead of silently removing the field from it, an accounting bug is then
+    /// detected. Disabled by default.
+    pub fn strict_field_distribution(&mut self, strict: bool) {
+        self.strict_field_distribution = strict;
+    }
+
     pub fn execute(self) -> Result<u64> {
+        self.execute_detailed().map(|result| result.deleted_documents)
+    }
+
+    /// Deletes the documents like [`DeleteDocuments::execute`] does, but also returns the
+    /// external ids of the documents that were actually in the index and got removed.
+    pub fn execute_detailed(self) -> Result<DocumentDeletionResult> {
         self.index.set_updated_at(self.wtxn, &Utc::now())?;
         // We retrieve the current documents ids that are in the database.
         let mut documents_ids = self.index.documents_ids(self.wtxn)?;
 
         // We can and must stop removing documents in a database that is empty.
         if documents_ids.is_empty() {
-            return Ok(0);
+            return Ok(DocumentDeletionResult { deleted_documents: 0, external_ids: Vec::new() });
         }
 
         // We remove the documents ids that we want to delete
@@ -75,7 +110,14 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
         // We can execute a ClearDocuments operation when the number of documents
         // to delete is exactly the number of documents in the database.
         if current_documents_ids_len == self.documents_ids.len() {
-            return ClearDocuments::new(self.wtxn, self.index, self.update_id).execute();
+            let external_ids = self
+                .external_documents_ids
+                .into_stream()
+                .map(|result| result.map(|(external_id, _docid)| external_id))
+                .collect::<Result<_>>()?;
+            let deleted_documents =
+                ClearDocuments::new(self.wtxn, self.index, self.update_id).execute()?;
+            return Ok(DocumentDeletionResult { deleted_documents, external_ids });
         }
 
         let fields_ids_map = self.index.fields_ids_map(self.wtxn)?;
@@ -90,7 +132,12 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
         // is empty and it should be safe to return that we deleted 0 documents.
         let id_field = match fields_ids_map.id(primary_key) {
             Some(field) => field,
-            None => return Ok(0),
+            None => {
+                return Ok(DocumentDeletionResult {
+                    deleted_documents: 0,
+                    external_ids: Vec::new(),
+                })
+            }
         };
 
         let Index {
@@ -129,12 +176,13 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
                 }
 
                 if let Some(content) = obkv.get(id_field) {
-                    let external_id = match serde_json::from_slice(content).unwrap() {
-                        Value::String(string) => SmallString32::from(string.as_str()),
-                        Value::Number(number) => SmallString32::from(number.to_string()),
-                        document_id => {
-                            return Err(UserError::InvalidDocumentId { document_id }.into())
-                        }
+                    let document_id: Value = serde_json::from_slice(content).unwrap();
+                    // The policy is not applied, it may have been made stricter since.
+                    let external_id = PrimaryKeyPolicy::indexed_document_id(&document_id)
+                        .map(|external_id| SmallString32::from(external_id.as_ref()));
+                    let external_id = match external_id {
+                        Some(external_id) => external_id,
+                        None => return Err(UserError::InvalidDocumentId { document_id }.into()),
                     };
                     external_ids.push(external_id);
                 }
@@ -161,13 +209,35 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
         // to reflect deleted documents.
         // If all field occurrences are removed, delete the entry from distribution.
         // Otherwise, insert new number of occurrences (current_count - count_diff).
+        // In strict mode, removing more occurrences than counted is an error.
         for (field_id, count_diff) in fields_ids_distribution_diff {
             let field_name = fields_ids_map.name(field_id).unwrap();
-            if let Entry::Occupied(mut entry) = field_distribution.entry(field_name.to_string()) {
-                match entry.get().checked_sub(count_diff) {
-                    Some(0) | None => entry.remove(),
-                    Some(count) => entry.insert(count),
-                };
+            match field_distribution.entry(field_name.to_string()) {
+                Entry::Occupied(mut entry) => match entry.get().checked_sub(count_diff) {
+                    None if self.strict_field_distribution => {
+                        return Err(InternalError::FieldDistributionUnderflow {
+                            field: field_name.to_string(),
+                            stored: *entry.get(),
+                            diff: count_diff,
+                        }
+                        .into());
+                    }
+                    Some(0) | None => {
+                        entry.remove();
+                    }
+                    Some(count) => {
+                        entry.insert(count);
+                    }
+                },
+                Entry::Vacant(_) if self.strict_field_distribution => {
+                    return Err(InternalError::FieldDistributionUnderflow {
+                        field: field_name.to_string(),
+                        stored: 0,
+                        diff: count_diff,
+                    }
+                    .into());
+                }
+                Entry::Vacant(_) => (),
             }
         }
 
@@ -422,7 +492,16 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
             )?;
         }
 
-        Ok(self.documents_ids.len())
+        // Remove the documents ids from the documents ids of the languages.
+        for (language, mut docids) in self.index.languages_documents_ids(self.wtxn)? {
+            docids -= &self.documents_ids;
+            self.index.put_language_documents_ids(self.wtxn, &language, &docids)?;
+        }
+
+        Ok(DocumentDeletionResult {
+            deleted_documents: self.documents_ids.len(),
+            external_ids: external_ids.into_iter().map(|id| id.to_string()).collect(),
+        })
     }
 }
 
@@ -548,7 +627,6 @@ mod tests {
 
     use super::*;
     use crate::update::{IndexDocuments, Settings, UpdateFormat};
-    use crate::FilterCondition;
 
     #[test]
     fn delete_documents_with_numbers_as_primary_key() {
@@ -657,4 +735,134 @@ mod tests {
 
         wtxn.commit().unwrap();
     }
+
+    #[test]
+    fn strict_field_distribution_underflow() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": 0, "name": "kevin" },
+            { "id": 1, "name": "kevina" },
+            { "id": 2, "name": "benoit" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        // We corrupt the field distribution, only one name is counted.
+        let mut field_distribution = index.field_distribution(&wtxn).unwrap();
+        field_distribution.insert(S("name"), 1);
+        index.put_field_distribution(&mut wtxn, &field_distribution).unwrap();
+        wtxn.commit().unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 1).unwrap();
+        builder.delete_external_id("0");
+        builder.delete_external_id("1");
+        builder.strict_field_distribution(true);
+        let error = builder.execute().unwrap_err();
+        match error {
+            crate::Error::InternalError(InternalError::FieldDistributionUnderflow {
+                field,
+                stored,
+                diff,
+            }) => {
+                assert_eq!(field, "name");
+                assert_eq!((stored, diff), (1, 2));
+            }
+            error => panic!("unexpected error {}", error),
+        }
+        drop(wtxn);
+
+        // The lenient mode silently removes the field from the distribution.
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 1).unwrap();
+        builder.delete_external_id("0");
+        builder.delete_external_id("1");
+        builder.execute().unwrap();
+        let field_distribution = index.field_distribution(&wtxn).unwrap();
+        assert!(!field_distribution.contains_key("name"));
+        assert_eq!(field_distribution.get("id"), Some(&1));
+        wtxn.commit().unwrap();
+    }
+
+    #[test]
+    fn execute_detailed_returns_the_external_ids() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": "kevin" }, { "id": "kevina" }, { "id": "benoit" }, { "id": "bernard" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 1).unwrap();
+        assert!(builder.delete_external_id("kevina").is_some());
+        assert!(builder.delete_external_id("unknown").is_none());
+        assert!(builder.delete_external_id("benoit").is_some());
+        let result = builder.execute_detailed().unwrap();
+        assert_eq!(result.deleted_documents, 2);
+        assert_eq!(result.external_ids, vec![S("benoit"), S("kevina")]);
+
+        // Deleting all the remaining documents clears the index.
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 2).unwrap();
+        assert!(builder.delete_external_id("kevin").is_some());
+        assert!(builder.delete_external_id("bernard").is_some());
+        let result = builder.execute_detailed().unwrap();
+        assert_eq!(result.deleted_documents, 2);
+        assert_eq!(result.external_ids, vec![S("bernard"), S("kevin")]);
+        wtxn.commit().unwrap();
+    }
+
+    #[test]
+    fn delete_by_filter() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("status") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 0, "status": "expired" },
+            { "id": 1, "status": "active" },
+            { "id": 2, "status": "expired" },
+            { "id": 3, "status": "active" },
+            { "id": 4, "status": "active" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        // The filter composes with the documents deleted by id, before and after it.
+        let condition = FilterCondition::from_str(&wtxn, &index, "status = expired").unwrap();
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 2).unwrap();
+        builder.delete_external_id("1");
+        builder.delete_by_filter(condition).unwrap();
+        builder.delete_external_id("3");
+        assert_eq!(builder.execute().unwrap(), 4);
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+        assert_eq!(external_documents_ids.get("0"), None);
+        assert_eq!(external_documents_ids.get("2"), None);
+        assert!(external_documents_ids.get("4").is_some());
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 1);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "status = expired").unwrap();
+        assert!(condition.evaluate(&rtxn, &index).unwrap().is_empty());
+    }
 }
@@ -13,6 +13,16 @@ use crate::error::{InternalError, SerializationError};
 use crate::proximity::ONE_ATTRIBUTE;
 use crate::{FieldId, Result};
 
+/// The fields from which the words are extracted and how they are extracted.
+#[derive(Debug, Default)]
+pub struct SearchableFields {
+    /// The searchable fields of the documents, all the fields when `None`.
+    pub fields: Option<HashSet<FieldId>>,
+    /// The maximum number of consecutive words that are concatenated and
+    /// indexed as a single word, no concatenation is done when `None`.
+    pub max_ngram: Option<usize>,
+}
+
 /// Extracts the word and positions where this word appear and
 /// prefixes it by the document id.
 ///
@@ -22,7 +32,7 @@ use crate::{FieldId, Result};
 pub fn extract_docid_word_positions<R: io::Read>(
     mut obkv_documents: grenad::Reader<R>,
     indexer: GrenadParameters,
-    searchable_fields: &Option<HashSet<FieldId>>,
+    searchable_fields: &SearchableFields,
     stop_words: Option<&fst::Set<&[u8]>>,
 ) -> Result<(RoaringBitmap, grenad::Reader<File>)> {
     let max_memory = indexer.max_memory_by_thread();
@@ -38,6 +48,7 @@ pub fn extract_docid_word_positions<R: io::Read>(
 
     let mut key_buffer = Vec::new();
     let mut field_buffer = String::new();
+    let mut words_buffer: Vec<(u32, String)> = Vec::new();
     let mut config = AnalyzerConfig::default();
     if let Some(stop_words) = stop_words {
         config.stop_words(stop_words);
@@ -56,7 +67,7 @@ pub fn extract_docid_word_positions<R: io::Read>(
         key_buffer.extend_from_slice(&document_id.to_be_bytes());
 
         for (field_id, field_bytes) in obkv.iter() {
-            if searchable_fields.as_ref().map_or(true, |sf| sf.contains(&field_id)) {
+            if searchable_fields.fields.as_ref().map_or(true, |sf| sf.contains(&field_id)) {
                 let value =
                     serde_json::from_slice(field_bytes).map_err(InternalError::SerdeJson)?;
                 field_buffer.clear();
@@ -65,6 +76,7 @@ pub fn extract_docid_word_positions<R: io::Read>(
                     let tokens = process_tokens(analyzed.tokens())
                         .take_while(|(p, _)| (*p as u32) < ONE_ATTRIBUTE);
 
+                    words_buffer.clear();
                     for (index, token) in tokens {
                         let token = token.text().trim();
                         if !token.is_empty() {
@@ -77,6 +89,19 @@ pub fn extract_docid_word_positions<R: io::Read>(
                             let position = field_id as u32 * ONE_ATTRIBUTE + position;
                             docid_word_positions_sorter
                                 .insert(&key_buffer, &position.to_ne_bytes())?;
+
+                            if searchable_fields.max_ngram.is_some() {
+                                words_buffer.push((position, token.to_string()));
+                            }
+                        }
+                    }
+
+                    if let Some(max_ngram) = searchable_fields.max_ngram {
+                        for (position, ngram) in ngrams(&words_buffer, max_ngram) {
+                            key_buffer.truncate(mem::size_of::<u32>());
+                            key_buffer.extend_from_slice(ngram.as_bytes());
+                            docid_word_positions_sorter
+                                .insert(&key_buffer, &position.to_ne_bytes())?;
                         }
                     }
                 }
@@ -87,6 +112,28 @@ pub fn extract_docid_word_positions<R: io::Read>(
     sorter_into_reader(docid_word_positions_sorter, indexer).map(|reader| (documents_ids, reader))
 }
 
+/// Concatenates the consecutive words, up to `max_ngram` of them, the words separated by a
+/// hard separator are not concatenated. The ngrams are positioned at their first word.
+fn ngrams(words: &[(u32, String)], max_ngram: usize) -> impl Iterator<Item = (u32, String)> + '_ {
+    (0..words.len()).flat_map(move |start| {
+        let (position, first) = &words[start];
+        let mut ngram = first.clone();
+        let mut last_position = *position;
+        words[start + 1..]
+            .iter()
+            .take(max_ngram.saturating_sub(1))
+            .take_while(move |(position, _)| {
+                let consecutive = *position == last_position + 1;
+                last_position = *position;
+                consecutive
+            })
+            .map(move |(_, word)| {
+                ngram.push_str(word);
+                (*position, ngram.clone())
+            })
+    })
+}
+
 /// Transform a JSON value into a string that can be indexed.
 fn json_to_string<'a>(value: &'a Value, buffer: &'a mut String) -> Option<&'a str> {
     fn inner(value: &Value, output: &mut String) -> bool {
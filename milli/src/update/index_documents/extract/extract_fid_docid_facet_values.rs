@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io;
 use std::mem::size_of;
@@ -7,11 +7,26 @@ use heed::zerocopy::AsBytes;
 use serde_json::Value;
 
 use super::helpers::{create_sorter, keep_first, sorter_into_reader, GrenadParameters};
-use crate::error::InternalError;
+use crate::error::{InternalError, UserError};
+use crate::facet::parse_date_millis;
 use crate::facet::value_encoding::f64_into_bytes;
 use crate::{DocumentId, FieldId, Result};
 
-/// Extracts the facet values of each faceted field of each document.
+/// The fields from which the facet values are extracted and how they are extracted.
+#[derive(Debug, Default)]
+pub struct FacetedFields {
+    /// The faceted fields of the documents.
+    pub fields: HashSet<FieldId>,
+    /// The faceted fields that are paths into a document field, grouped by this field.
+    pub nested_fields: HashMap<FieldId, Vec<(FieldId, String)>>,
+    /// The maximum number of characters of the facet strings, longer ones are truncated.
+    pub max_value_length: Option<usize>,
+    /// The faceted fields whose strings are dates indexed as numbers, with their names.
+    pub date_fields: HashMap<FieldId, String>,
+}
+
+/// Extracts the facet values of each faceted field of each document, the nested faceted
+/// fields are extracted from the values found under their path in the document fields.
 ///
 /// Returns the generated grenad reader containing the docid the fid and the orginal value as key
 /// and the normalized value as value extracted from the given chunk of documents.
@@ -19,7 +34,7 @@ use crate::{DocumentId, FieldId, Result};
 pub fn extract_fid_docid_facet_values<R: io::Read>(
     mut obkv_documents: grenad::Reader<R>,
     indexer: GrenadParameters,
-    faceted_fields: &HashSet<FieldId>,
+    faceted_fields: &FacetedFields,
 ) -> Result<(grenad::Reader<File>, grenad::Reader<File>)> {
     let max_memory = indexer.max_memory_by_thread();
 
@@ -44,10 +59,43 @@ pub fn extract_fid_docid_facet_values<R: io::Read>(
         let obkv = obkv::KvReader::new(value);
 
         for (field_id, field_bytes) in obkv.iter() {
-            if faceted_fields.contains(&field_id) {
-                let value =
-                    serde_json::from_slice(field_bytes).map_err(InternalError::SerdeJson)?;
-                let (numbers, strings) = extract_facet_values(&value);
+            let nested_fields = faceted_fields.nested_fields.get(&field_id);
+            if !faceted_fields.fields.contains(&field_id) && nested_fields.is_none() {
+                continue;
+            }
+
+            let value: Value =
+                serde_json::from_slice(field_bytes).map_err(InternalError::SerdeJson)?;
+
+            let mut facet_values = Vec::new();
+            if faceted_fields.fields.contains(&field_id) {
+                facet_values.push((field_id, extract_facet_values(&value)));
+            }
+            for (nested_field_id, path) in nested_fields.into_iter().flatten() {
+                let mut numbers = Vec::new();
+                let mut strings = Vec::new();
+                for nested_value in values_at_path(&value, path) {
+                    let (n, s) = extract_facet_values(nested_value);
+                    numbers.extend(n);
+                    strings.extend(s);
+                }
+                facet_values.push((*nested_field_id, (numbers, strings)));
+            }
+
+            for (field_id, (mut numbers, mut strings)) in facet_values {
+                if let Some(field) = faceted_fields.date_fields.get(&field_id) {
+                    for (_, original) in strings.drain(..) {
+                        match parse_date_millis(&original) {
+                            Some(millis) => numbers.push(millis as f64),
+                            None => {
+                                let field = field.clone();
+                                let error =
+                                    UserError::InvalidDateFacetValue { field, value: original };
+                                return Err(error.into());
+                            }
+                        }
+                    }
+                }
 
                 key_buffer.clear();
 
@@ -68,6 +116,12 @@ pub fn extract_fid_docid_facet_values<R: io::Read>(
 
                 // insert  normalized and original facet string in sorter
                 for (normalized, original) in strings.into_iter().filter(|(n, _)| !n.is_empty()) {
+                    let (normalized, original) = match faceted_fields.max_value_length {
+                        Some(max) => {
+                            (truncate_chars(&normalized, max), truncate_chars(&original, max))
+                        }
+                        None => (normalized.as_str(), original.as_str()),
+                    };
                     key_buffer.truncate(size_of::<FieldId>() + size_of::<DocumentId>());
                     key_buffer.extend_from_slice(normalized.as_bytes());
                     fid_docid_facet_strings_sorter.insert(&key_buffer, original.as_bytes())?;
@@ -82,6 +136,39 @@ pub fn extract_fid_docid_facet_values<R: io::Read>(
     ))
 }
 
+/// Returns the first `max` characters of the string.
+fn truncate_chars(string: &str, max: usize) -> &str {
+    match string.char_indices().nth(max) {
+        Some((index, _)) => &string[..index],
+        None => string,
+    }
+}
+
+/// Returns the values found under the dotted path, going through the arrays elements.
+fn values_at_path<'v>(value: &'v Value, path: &str) -> Vec<&'v Value> {
+    fn inner_values_at_path<'v>(value: &'v Value, path: &[&str], output: &mut Vec<&'v Value>) {
+        match (value, path.split_first()) {
+            (Value::Array(values), _) => {
+                for value in values {
+                    inner_values_at_path(value, path, output);
+                }
+            }
+            (Value::Object(object), Some((key, rest))) => {
+                if let Some(value) = object.get(*key) {
+                    inner_values_at_path(value, rest, output);
+                }
+            }
+            (value, None) => output.push(value),
+            (_, Some(_)) => (),
+        }
+    }
+
+    let path: Vec<_> = path.split('.').collect();
+    let mut output = Vec::new();
+    inner_values_at_path(value, &path, &mut output);
+    output
+}
+
 fn extract_facet_values(value: &Value) -> (Vec<f64>, Vec<(String, String)>) {
     fn inner_extract_facet_values(
         value: &Value,
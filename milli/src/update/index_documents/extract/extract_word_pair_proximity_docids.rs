@@ -15,12 +15,17 @@ use crate::{DocumentId, Result};
 /// Extracts the best proximity between pairs of words and the documents ids where this pair appear.
 ///
 /// Returns a grenad reader with the list of extracted word pairs proximities and
-/// documents ids from the given chunk of docid word positions.
+/// documents ids from the given chunk of docid word positions, the pairs farther
+/// than `max_proximity_distance` are ignored.
 #[logging_timer::time]
 pub fn extract_word_pair_proximity_docids<R: io::Read>(
     mut docid_word_positions: grenad::Reader<R>,
     indexer: GrenadParameters,
+    max_proximity_distance: u8,
 ) -> Result<grenad::Reader<File>> {
+    // The proximities are never higher than this value anyway.
+    let max_proximity = cmp::min(max_proximity_distance as u32, MAX_DISTANCE - 1);
+
     let max_memory = indexer.max_memory_by_thread();
 
     let mut word_pair_proximity_docids_sorter = create_sorter(
@@ -47,6 +52,7 @@ pub fn extract_word_pair_proximity_docids<R: io::Read>(
             document_word_positions_into_sorter(
                 curr_document_id,
                 document_word_positions_heap,
+                max_proximity,
                 &mut word_pair_proximity_docids_sorter,
             )?;
             current_document_id = Some(document_id);
@@ -66,6 +72,7 @@ pub fn extract_word_pair_proximity_docids<R: io::Read>(
         document_word_positions_into_sorter(
             document_id,
             document_word_positions_heap,
+            max_proximity,
             &mut word_pair_proximity_docids_sorter,
         )?;
     }
@@ -73,13 +80,15 @@ pub fn extract_word_pair_proximity_docids<R: io::Read>(
     sorter_into_reader(word_pair_proximity_docids_sorter, indexer)
 }
 
-/// Fills the list of all pairs of words with the shortest proximity between 1 and 7 inclusive.
+/// Fills the list of all pairs of words with the shortest proximity between 1 and
+/// `max_proximity` inclusive, which is at most 7.
 ///
 /// This list is used by the engine to calculate the documents containing words that are
 /// close to each other.
 fn document_word_positions_into_sorter<'b>(
     document_id: DocumentId,
     mut word_positions_heap: BinaryHeap<PeekedWordPosition<vec::IntoIter<u32>>>,
+    max_proximity: u32,
     word_pair_proximity_docids_sorter: &mut grenad::Sorter<MergeFn>,
 ) -> Result<()> {
     let mut word_pair_proximity = HashMap::new();
@@ -95,7 +104,7 @@ fn document_word_positions_into_sorter<'b>(
         if let Some((head, tail)) = ordered_peeked_word_positions.split_first() {
             for PeekedWordPosition { word, position, .. } in tail {
                 let prox = positions_proximity(head.position, *position);
-                if prox > 0 && prox < MAX_DISTANCE {
+                if prox > 0 && prox <= max_proximity {
                     word_pair_proximity
                         .entry((head.word.clone(), word.clone()))
                         .and_modify(|p| {
@@ -105,7 +114,7 @@ fn document_word_positions_into_sorter<'b>(
 
                     // We also compute the inverse proximity.
                     let prox = prox + 1;
-                    if prox < MAX_DISTANCE {
+                    if prox <= max_proximity {
                         word_pair_proximity
                             .entry((word.clone(), head.word.clone()))
                             .and_modify(|p| {
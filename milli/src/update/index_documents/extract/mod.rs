@@ -7,7 +7,6 @@ mod extract_word_docids;
 mod extract_word_level_position_docids;
 mod extract_word_pair_proximity_docids;
 
-use std::collections::HashSet;
 use std::fs::File;
 
 use crossbeam_channel::Sender;
@@ -15,9 +14,11 @@ use log::debug;
 use rayon::prelude::*;
 
 use self::extract_docid_word_positions::extract_docid_word_positions;
+pub(crate) use self::extract_docid_word_positions::SearchableFields;
 use self::extract_facet_number_docids::extract_facet_number_docids;
 use self::extract_facet_string_docids::extract_facet_string_docids;
 use self::extract_fid_docid_facet_values::extract_fid_docid_facet_values;
+pub(crate) use self::extract_fid_docid_facet_values::FacetedFields;
 use self::extract_fid_word_count_docids::extract_fid_word_count_docids;
 use self::extract_word_docids::extract_word_docids;
 use self::extract_word_level_position_docids::extract_word_level_position_docids;
@@ -27,7 +28,7 @@ use super::helpers::{
     merge_readers, merge_roaring_bitmaps, CursorClonableMmap, GrenadParameters, MergeFn,
 };
 use super::{helpers, TypedChunk};
-use crate::{FieldId, Result};
+use crate::Result;
 
 /// Extract data for each databases from obkv documents in parallel.
 /// Send data in grenad file over provided Sender.
@@ -35,9 +36,10 @@ pub(crate) fn data_from_obkv_documents(
     obkv_chunks: impl Iterator<Item = Result<grenad::Reader<File>>> + Send,
     indexer: GrenadParameters,
     lmdb_writer_sx: Sender<Result<TypedChunk>>,
-    searchable_fields: Option<HashSet<FieldId>>,
-    faceted_fields: HashSet<FieldId>,
+    searchable_fields: SearchableFields,
+    faceted_fields: FacetedFields,
     stop_words: Option<fst::Set<&[u8]>>,
+    max_proximity_distance: u8,
 ) -> Result<()> {
     let result: Result<(Vec<_>, (Vec<_>, Vec<_>))> = obkv_chunks
         .par_bridge()
@@ -62,7 +64,9 @@ pub(crate) fn data_from_obkv_documents(
         docid_word_positions_chunks.clone(),
         indexer.clone(),
         lmdb_writer_sx.clone(),
-        extract_word_pair_proximity_docids,
+        move |chunk, indexer| {
+            extract_word_pair_proximity_docids(chunk, indexer, max_proximity_distance)
+        },
         merge_cbo_roaring_bitmaps,
         TypedChunk::WordPairProximityDocids,
         "word-pair-proximity-docids",
@@ -166,8 +170,8 @@ fn extract_documents_data(
     documents_chunk: Result<grenad::Reader<File>>,
     indexer: GrenadParameters,
     lmdb_writer_sx: Sender<Result<TypedChunk>>,
-    searchable_fields: &Option<HashSet<FieldId>>,
-    faceted_fields: &HashSet<FieldId>,
+    searchable_fields: &SearchableFields,
+    faceted_fields: &FacetedFields,
     stop_words: &Option<fst::Set<&[u8]>>,
 ) -> Result<(
     grenad::Reader<CursorClonableMmap>,
@@ -260,3 +260,77 @@ fn merger_iter_into_lmdb_database<R: io::Read>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use heed::EnvOpenOptions;
+
+    use super::*;
+    use crate::update::index_documents::helpers::read_u32_ne_bytes;
+
+    /// Merges sorted arrays of integers into a sorted and deduplicated one.
+    fn merge_sorted_u32s<'a>(_key: &[u8], values: &[Cow<'a, [u8]>]) -> Result<Cow<'a, [u8]>> {
+        if values.len() == 1 {
+            return Ok(values[0].clone());
+        }
+
+        let mut integers: Vec<_> = values.iter().flat_map(|v| read_u32_ne_bytes(v)).collect();
+        integers.sort_unstable();
+        integers.dedup();
+
+        let mut output = Vec::with_capacity(integers.len() * 4);
+        integers.iter().for_each(|i| output.extend_from_slice(&i.to_ne_bytes()));
+        Ok(Cow::Owned(output))
+    }
+
+    fn u32s_reader(entries: &[(&str, &[u32])]) -> Reader<File> {
+        let mut writer =
+            create_writer(CompressionType::None, None, tempfile::tempfile().unwrap()).unwrap();
+        for (key, integers) in entries {
+            let value: Vec<_> = integers.iter().flat_map(|i| i.to_ne_bytes().to_vec()).collect();
+            writer.insert(key.as_bytes(), value).unwrap();
+        }
+        writer_into_reader(writer).unwrap()
+    }
+
+    #[test]
+    fn write_into_lmdb_database_with_custom_merge() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        options.max_dbs(1);
+        let env = options.open(&path).unwrap();
+        let database = env.create_poly_database(Some("integers")).unwrap();
+
+        let mut wtxn = env.write_txn().unwrap();
+        let reader = u32s_reader(&[("hello", &[1, 2, 3]), ("world", &[4])]);
+        write_into_lmdb_database(
+            &mut wtxn,
+            database,
+            reader,
+            merge_sorted_u32s,
+            WriteMethod::Append,
+        )
+        .unwrap();
+
+        let reader = u32s_reader(&[("hello", &[2, 3, 5]), ("kiki", &[1])]);
+        write_into_lmdb_database(
+            &mut wtxn,
+            database,
+            reader,
+            merge_sorted_u32s,
+            WriteMethod::GetMergePut,
+        )
+        .unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = env.read_txn().unwrap();
+        let get = |key: &str| -> Vec<u32> {
+            let bytes = database.get::<_, ByteSlice, ByteSlice>(&rtxn, key.as_bytes()).unwrap();
+            read_u32_ne_bytes(bytes.unwrap()).collect()
+        };
+        assert_eq!(get("hello"), vec![1, 2, 3, 5]);
+        assert_eq!(get("kiki"), vec![1]);
+        assert_eq!(get("world"), vec![4]);
+    }
+}
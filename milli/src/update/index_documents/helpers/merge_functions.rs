@@ -9,6 +9,13 @@ use crate::heed_codec::facet::{decode_prefix_string, encode_prefix_string};
 use crate::heed_codec::CboRoaringBitmapCodec;
 use crate::Result;
 
+/// A function that merges the different values associated with the same key.
+///
+/// A merge function must uphold some invariants to be used during indexing:
+///  - it is called with at least one value and must return it unchanged if there is only one,
+///  - the values are given in insertion order, the oldest value first,
+///  - it must be associative, as values can be merged in multiple passes, in any grouping,
+///  - the returned value must be decodable by the codec of the target database.
 pub type MergeFn = for<'a> fn(&[u8], &[Cow<'a, [u8]>]) -> Result<Cow<'a, [u8]>>;
 
 pub fn concat_u32s_array<'a>(_key: &[u8], values: &[Cow<'a, [u8]>]) -> Result<Cow<'a, [u8]>> {
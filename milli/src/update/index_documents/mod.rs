@@ -3,9 +3,10 @@ mod helpers;
 mod transform;
 mod typed_chunk;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, BufRead, BufReader};
 use std::iter::FromIterator;
+use std::mem;
 use std::num::{NonZeroU32, NonZeroUsize};
 use std::time::Instant;
 
@@ -18,17 +19,20 @@ use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use typed_chunk::{write_typed_chunk_into_index, TypedChunk};
 
+use self::extract::{FacetedFields, SearchableFields};
 pub use self::helpers::{
     create_sorter, create_writer, merge_cbo_roaring_bitmaps, merge_roaring_bitmaps,
     sorter_into_lmdb_database, write_into_lmdb_database, writer_into_reader, MergeFn,
 };
 use self::helpers::{grenad_obkv_into_chunks, GrenadParameters};
+pub(crate) use self::transform::extract_external_id;
 pub use self::transform::{Transform, TransformOutput};
+use crate::error::UserError;
 use crate::update::{
     Facets, UpdateBuilder, UpdateIndexingStep, WordPrefixDocids, WordPrefixPairProximityDocids,
     WordsLevelPositions, WordsPrefixesFst,
 };
-use crate::{Index, Result};
+use crate::{DocumentId, FieldId, FieldsIdsMap, Index, Result};
 
 static MERGED_DATABASE_COUNT: usize = 7;
 static PREFIX_DATABASE_COUNT: usize = 5;
@@ -36,7 +40,21 @@ static TOTAL_POSTING_DATABASE_COUNT: usize = MERGED_DATABASE_COUNT + PREFIX_DATA
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DocumentAdditionResult {
+    /// The number of documents in the update, after deduplication.
     pub nb_documents: usize,
+    /// The number of documents that were not in the index before this update.
+    #[serde(default)]
+    pub nb_new_documents: usize,
+    /// The number of documents that were already in the index and were replaced
+    /// or updated, depending on the `IndexDocumentsMethod`.
+    #[serde(default)]
+    pub nb_replaced_documents: usize,
+    /// The number of documents skipped because they were missing the primary key.
+    #[serde(default)]
+    pub nb_skipped_missing_primary_key: usize,
+    /// The number of documents skipped because their document id was invalid.
+    #[serde(default)]
+    pub nb_skipped_invalid_document_id: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -51,9 +69,14 @@ pub enum IndexDocumentsMethod {
     UpdateDocuments,
 }
 
+/// The way entries are written into an LMDB database.
 #[derive(Debug, Copy, Clone)]
 pub enum WriteMethod {
+    /// Appends the entries at the end of the database, the database must be empty
+    /// or only contain keys that are lower than the ones being written.
     Append,
+    /// Merges the entries with the already existing ones,
+    /// using the given merge function when a key is already present.
     GetMergePut,
 }
 
@@ -68,6 +91,39 @@ pub enum UpdateFormat {
     JsonStream,
 }
 
+/// Gives a field id to the faceted fields that are dotted paths into the objects, or arrays
+/// of objects, of a document field, e.g. `variants.color`. Returns these field ids and paths
+/// grouped by the id of the document field they point into.
+///
+/// A document is faceted by all the values found under the path, in every element of the
+/// arrays, it therefore matches a filter on the path if any of these values matches.
+fn nested_faceted_fields(
+    faceted_fields: &HashSet<String>,
+    fields_ids_map: &mut FieldsIdsMap,
+) -> Result<HashMap<FieldId, Vec<(FieldId, String)>>> {
+    let mut nested_faceted_fields = HashMap::new();
+    for name in faceted_fields {
+        if let Some(index) = name.find('.') {
+            let (parent, path) = (&name[..index], &name[index + 1..]);
+            if let Some(parent_id) = fields_ids_map.id(parent) {
+                let field_id =
+                    fields_ids_map.insert(name).ok_or(UserError::AttributeLimitReached)?;
+                nested_faceted_fields
+                    .entry(parent_id)
+                    .or_insert_with(Vec::new)
+                    .push((field_id, path.to_string()));
+            }
+        }
+    }
+    Ok(nested_faceted_fields)
+}
+
+/// A function called with an indexed document id and its words with their positions,
+/// it inserts the entries of a derived database into the given sorter.
+pub type DerivedDatabaseExtractor<'a> = Box<
+    dyn Fn(DocumentId, &[(String, RoaringBitmap)], &mut grenad::Sorter<MergeFn>) -> Result<()> + 'a,
+>;
+
 pub struct IndexDocuments<'t, 'u, 'i, 'a> {
     wtxn: &'t mut heed::RwTxn<'i, 'u>,
     index: &'i Index,
@@ -87,6 +143,11 @@ pub struct IndexDocuments<'t, 'u, 'i, 'a> {
     update_method: IndexDocumentsMethod,
     update_format: UpdateFormat,
     autogenerate_docids: bool,
+    skip_invalid_documents: bool,
+    csv_delimiter: u8,
+    assume_empty_index: bool,
+    language: Option<String>,
+    derived_databases: Vec<(heed::PolyDatabase, MergeFn, DerivedDatabaseExtractor<'a>)>,
     update_id: u64,
 }
 
@@ -115,6 +176,11 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
             update_method: IndexDocumentsMethod::ReplaceDocuments,
             update_format: UpdateFormat::Json,
             autogenerate_docids: false,
+            skip_invalid_documents: false,
+            csv_delimiter: b',',
+            assume_empty_index: false,
+            language: None,
+            derived_databases: Vec::new(),
             update_id,
         }
     }
@@ -135,6 +201,57 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
         self.autogenerate_docids = false;
     }
 
+    /// Skips the documents with a missing primary key or an invalid document id instead of
+    /// failing the whole update, the number of skipped documents is reported in the result.
+    pub fn skip_invalid_documents(&mut self, skip: bool) {
+        self.skip_invalid_documents = skip;
+    }
+
+    /// The byte separating the fields of the CSV records, a comma by default.
+    ///
+    /// The CSV headers can declare the type of their column with a `:number` or a
+    /// `:boolean` suffix, e.g. `price:number`, the cells of those columns are converted
+    /// into JSON numbers or booleans and the suffix is removed from the field name.
+    pub fn csv_delimiter(&mut self, delimiter: u8) {
+        self.csv_delimiter = delimiter;
+    }
+
+    /// Always appends the entries at the end of the derived databases, without merging them
+    /// with already existing ones, this is only valid for a first load into an empty index.
+    ///
+    /// The append method is already selected when the index contains no documents, this
+    /// makes the intent explicit and asserts that the index is empty in debug builds.
+    pub fn assume_empty_index(&mut self, assume: bool) {
+        self.assume_empty_index = assume;
+    }
+
+    /// Uses the stop words registered for this language instead of the global ones.
+    pub fn language(&mut self, language: impl Into<String>) {
+        self.language = Some(language.into());
+    }
+
+    /// Fills a database owned by the application, created with
+    /// [`Index::create_derived_database`], from the words of the indexed documents.
+    ///
+    /// The extractor is called once for every document of this update with its words,
+    /// sorted, and their positions, reusing the tokenization done by the indexing. The
+    /// entries it inserts in the sorter are merged with `merge`, along with the entries
+    /// already in the database, once all the documents have been indexed.
+    ///
+    /// The database must not be one of the index databases, and the entries of the
+    /// replaced or deleted documents are not removed from it.
+    pub fn derived_database<F>(
+        &mut self,
+        database: heed::PolyDatabase,
+        merge: MergeFn,
+        extractor: F,
+    ) where
+        F: Fn(DocumentId, &[(String, RoaringBitmap)], &mut grenad::Sorter<MergeFn>) -> Result<()>
+            + 'a,
+    {
+        self.derived_databases.push((database, merge, Box::new(extractor)));
+    }
+
     #[logging_timer::time("IndexDocuments::{}")]
     pub fn execute<R, F>(self, reader: R, progress_callback: F) -> Result<DocumentAdditionResult>
     where
@@ -146,7 +263,13 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
 
         // Early return when there is no document to add
         if reader.buffer().is_empty() {
-            return Ok(DocumentAdditionResult { nb_documents: 0 });
+            return Ok(DocumentAdditionResult {
+                nb_documents: 0,
+                nb_new_documents: 0,
+                nb_replaced_documents: 0,
+                nb_skipped_missing_primary_key: 0,
+                nb_skipped_invalid_document_id: 0,
+            });
         }
 
         self.index.set_updated_at(self.wtxn, &Utc::now())?;
@@ -163,6 +286,8 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
             max_memory: self.max_memory,
             index_documents_method: self.update_method,
             autogenerate_docids: self.autogenerate_docids,
+            skip_invalid_documents: self.skip_invalid_documents,
+            csv_delimiter: self.csv_delimiter,
         };
 
         let output = match self.update_format {
@@ -174,29 +299,50 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
         };
 
         let nb_documents = output.documents_count;
+        let nb_new_documents = output.new_documents_ids.len() as usize;
+        let nb_replaced_documents = output.replaced_documents_ids.len() as usize;
+        let nb_skipped_missing_primary_key = output.skipped_missing_primary_key;
+        let nb_skipped_invalid_document_id = output.skipped_invalid_document_id;
 
         info!("Update transformed in {:.02?}", before_transform.elapsed());
 
         self.execute_raw(output, progress_callback)?;
-        Ok(DocumentAdditionResult { nb_documents })
+        Ok(DocumentAdditionResult {
+            nb_documents,
+            nb_new_documents,
+            nb_replaced_documents,
+            nb_skipped_missing_primary_key,
+            nb_skipped_invalid_document_id,
+        })
     }
 
     #[logging_timer::time("IndexDocuments::{}")]
-    pub fn execute_raw<F>(self, output: TransformOutput, progress_callback: F) -> Result<()>
+    pub fn execute_raw<F>(mut self, output: TransformOutput, progress_callback: F) -> Result<()>
     where
         F: Fn(UpdateIndexingStep) + Sync,
     {
         let TransformOutput {
             primary_key,
-            fields_ids_map,
+            mut fields_ids_map,
             field_distribution,
             external_documents_ids,
             new_documents_ids,
             replaced_documents_ids,
             documents_count,
             documents_file,
+            skipped_missing_primary_key: _,
+            skipped_invalid_document_id: _,
         } = output;
 
+        let faceted_fields = self.index.faceted_fields(self.wtxn)?;
+        let nested_faceted_fields = nested_faceted_fields(&faceted_fields, &mut fields_ids_map)?;
+        let date_fields: HashMap<_, _> = self
+            .index
+            .date_fields(self.wtxn)?
+            .into_iter()
+            .filter_map(|name| fields_ids_map.id(&name).map(|id| (id, name)))
+            .collect();
+
         // The fields_ids_map is put back to the store now so the rest of the transaction sees an
         // up to date field map.
         self.index.put_fields_ids_map(self.wtxn, &fields_ids_map)?;
@@ -229,13 +375,20 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
         ) = crossbeam_channel::unbounded();
 
         // get searchable fields for word databases
-        let searchable_fields =
-            self.index.searchable_fields_ids(self.wtxn)?.map(HashSet::from_iter);
+        let searchable_fields = SearchableFields {
+            fields: self.index.searchable_fields_ids(self.wtxn)?.map(HashSet::from_iter),
+            max_ngram: self.index.max_ngram(self.wtxn)?,
+        };
         // get filterable fields for facet databases
-        let faceted_fields = self.index.faceted_fields_ids(self.wtxn)?;
+        let faceted_fields = FacetedFields {
+            fields: self.index.faceted_fields_ids(self.wtxn)?,
+            nested_fields: nested_faceted_fields,
+            max_value_length: self.index.max_facet_value_length(self.wtxn)?,
+            date_fields,
+        };
 
-        let stop_words = self.index.stop_words(self.wtxn)?;
-        // let stop_words = stop_words.as_ref();
+        let stop_words = self.index.stop_words_for_language(self.wtxn, self.language.as_deref())?;
+        let max_proximity_distance = self.index.max_proximity_distance(self.wtxn)?;
 
         // Run extraction pipeline in parallel.
         pool.install(|| {
@@ -262,6 +415,7 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
                     searchable_fields,
                     faceted_fields,
                     stop_words,
+                    max_proximity_distance,
                 )
             });
 
@@ -294,7 +448,15 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
         }
 
         let index_documents_ids = self.index.documents_ids(self.wtxn)?;
-        let index_is_empty = index_documents_ids.len() == 0;
+        let index_is_empty = if self.assume_empty_index {
+            debug_assert!(
+                index_documents_ids.is_empty() && self.index.word_docids.is_empty(self.wtxn)?,
+                "the index is assumed to be empty but it contains documents"
+            );
+            true
+        } else {
+            index_documents_ids.is_empty()
+        };
         let mut final_documents_ids = RoaringBitmap::new();
 
         let mut databases_seen = 0;
@@ -336,12 +498,61 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
         // We write the external documents ids into the main database.
         self.index.put_external_documents_ids(self.wtxn, &external_documents_ids)?;
 
-        let all_documents_ids = index_documents_ids | new_documents_ids | replaced_documents_ids;
+        let indexed_documents_ids = new_documents_ids | replaced_documents_ids;
+        let all_documents_ids = index_documents_ids | &indexed_documents_ids;
         self.index.put_documents_ids(self.wtxn, &all_documents_ids)?;
 
+        // We keep track of the language hint the documents have been indexed with, they
+        // are indexed again with the stop words of this language when the settings change.
+        let mut languages = self.index.languages_documents_ids(self.wtxn)?;
+        for docids in languages.values_mut() {
+            *docids -= &indexed_documents_ids;
+        }
+        if let Some(language) = &self.language {
+            *languages.entry(language.clone()).or_default() |= &indexed_documents_ids;
+        }
+        for (language, docids) in &languages {
+            self.index.put_language_documents_ids(self.wtxn, language, docids)?;
+        }
+
+        self.write_derived_databases(&indexed_documents_ids)?;
+
         self.execute_prefix_databases(progress_callback)
     }
 
+    fn write_derived_databases(&mut self, documents_ids: &RoaringBitmap) -> Result<()> {
+        for (database, merge, extractor) in mem::take(&mut self.derived_databases) {
+            let mut sorter = create_sorter(
+                merge,
+                self.chunk_compression_type,
+                self.chunk_compression_level,
+                self.max_nb_chunks,
+                self.max_memory,
+            );
+
+            let mut words_positions = Vec::new();
+            for docid in documents_ids {
+                words_positions.clear();
+                let iter = self.index.docid_word_positions.prefix_iter(self.wtxn, &(docid, ""))?;
+                for result in iter {
+                    let ((_, word), positions) = result?;
+                    words_positions.push((word.to_string(), positions));
+                }
+                (extractor)(docid, &words_positions, &mut sorter)?;
+            }
+
+            sorter_into_lmdb_database(
+                self.wtxn,
+                database,
+                sorter,
+                merge,
+                WriteMethod::GetMergePut,
+            )?;
+        }
+
+        Ok(())
+    }
+
     #[logging_timer::time("IndexDocuments::{}")]
     pub fn execute_prefix_databases<F>(self, progress_callback: F) -> Result<()>
     where
@@ -418,6 +629,7 @@ impl<'t, 'u, 'i, 'a> IndexDocuments<'t, 'u, 'i, 'a> {
         builder.chunk_compression_level = self.chunk_compression_level;
         builder.max_nb_chunks = self.max_nb_chunks;
         builder.max_memory = self.max_memory;
+        builder.thread_pool = self.thread_pool;
         if let Some(value) = self.words_positions_level_group_size {
             builder.level_group_size(value);
         }
@@ -441,11 +653,81 @@ mod tests {
     use std::io::Cursor;
 
     use big_s::S;
+    use heed::types::Str;
     use heed::EnvOpenOptions;
+    use maplit::hashset;
 
     use super::*;
-    use crate::update::DeleteDocuments;
-    use crate::HashMap;
+    use crate::update::{DeleteDocuments, Settings};
+    use crate::{FilterCondition, HashMap, PrimaryKeyPolicy, RoaringBitmapCodec};
+
+    #[test]
+    fn derived_database() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+        let bigrams = index.create_derived_database("bigrams").unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": 1, "name": "kevin" },
+            { "id": 2, "name": "kevina keke" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.derived_database(bigrams, merge_roaring_bitmaps, |docid, words, sorter| {
+            let mut buffer = Vec::new();
+            RoaringBitmap::from_iter(Some(docid)).serialize_into(&mut buffer)?;
+            for (word, _positions) in words {
+                for bigram in word.as_bytes().windows(2) {
+                    sorter.insert(bigram, &buffer)?;
+                }
+            }
+            Ok(())
+        });
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let get = |key: &str| bigrams.get::<_, Str, RoaringBitmapCodec>(&rtxn, key).unwrap();
+        assert_eq!(get("ke"), Some(RoaringBitmap::from_iter(vec![0, 1])));
+        assert_eq!(get("vi"), Some(RoaringBitmap::from_iter(vec![0, 1])));
+        assert_eq!(get("na"), Some(RoaringBitmap::from_iter(vec![1])));
+        assert_eq!(get("ek"), Some(RoaringBitmap::from_iter(vec![1])));
+        assert_eq!(get("zz"), None);
+
+        // The index databases are untouched.
+        assert!(index.word_docids.get(&rtxn, "ke").unwrap().is_none());
+    }
+
+    #[test]
+    fn new_and_replaced_documents_counts() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &b"id,name\n1,kevin\n2,kevina\n3,benoit\n"[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Csv);
+        let result = builder.execute(content, |_, _| ()).unwrap();
+        assert_eq!(result.nb_documents, 3);
+        assert_eq!(result.nb_new_documents, 3);
+        assert_eq!(result.nb_replaced_documents, 0);
+
+        // Two of these documents are already in the index.
+        let content = &b"id,name\n2,updated kevina\n3,updated benoit\n4,tamo\n"[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Csv);
+        builder.index_documents_method(IndexDocumentsMethod::UpdateDocuments);
+        let result = builder.execute(content, |_, _| ()).unwrap();
+        assert_eq!(result.nb_documents, 3);
+        assert_eq!(result.nb_new_documents, 1);
+        assert_eq!(result.nb_replaced_documents, 2);
+        wtxn.commit().unwrap();
+    }
 
     #[test]
     fn simple_document_replacement() {
@@ -829,6 +1111,97 @@ mod tests {
         drop(rtxn);
     }
 
+    #[test]
+    fn assume_empty_index() {
+        let content = &br#"[
+            { "id": 1, "name": "kevin", "description": "the quick brown fox" },
+            { "id": 2, "name": "kevina", "description": "jumps over the lazy dog" },
+            { "id": 3, "name": "benoit", "description": "the quick dog" }
+        ]"#[..];
+
+        let build = |assume_empty_index: bool| {
+            let path = tempfile::tempdir().unwrap();
+            let mut options = EnvOpenOptions::new();
+            options.map_size(10 * 1024 * 1024); // 10 MB
+            let index = Index::new(options, &path).unwrap();
+
+            let mut wtxn = index.write_txn().unwrap();
+            let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+            builder.update_format(UpdateFormat::Json);
+            builder.assume_empty_index(assume_empty_index);
+            builder.execute(content, |_, _| ()).unwrap();
+            wtxn.commit().unwrap();
+
+            let rtxn = index.read_txn().unwrap();
+            let fingerprint = index.content_fingerprint(&rtxn).unwrap();
+            let word_docids: Vec<_> = index
+                .word_docids
+                .iter(&rtxn)
+                .unwrap()
+                .map(|result| result.map(|(word, docids)| (word.to_string(), docids)))
+                .collect::<heed::Result<_>>()
+                .unwrap();
+            let word_pair_proximity_docids = index.word_pair_proximity_docids.len(&rtxn).unwrap();
+            (fingerprint, word_docids, word_pair_proximity_docids)
+        };
+
+        assert_eq!(build(true), build(false));
+    }
+
+    #[test]
+    fn skip_invalid_documents() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "name": "kevin" },
+            { "name": "no id" },
+            { "id": { "nested": 2 }, "name": "bad type" },
+            { "id": "brume bleue", "name": "bad characters" },
+            { "name": "no id either", "age": 32 },
+            { "id": 2, "name": "kevina" }
+        ]"#[..];
+
+        // By default the whole batch fails.
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        assert!(builder.execute(content, |_, _| ()).is_err());
+        drop(wtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.skip_invalid_documents(true);
+        let result = builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        assert_eq!(result.nb_documents, 2);
+        assert_eq!(result.nb_skipped_missing_primary_key, 2);
+        assert_eq!(result.nb_skipped_invalid_document_id, 2);
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 2);
+        // The fields of the skipped documents are not registered.
+        assert!(index.fields_ids_map(&rtxn).unwrap().id("age").is_none());
+        drop(rtxn);
+
+        // An empty CSV cell is considered as a missing primary key.
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &b"id,name\n3,benoit\n,no id\nbrume bleue,bad characters\n"[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Csv);
+        builder.skip_invalid_documents(true);
+        let result = builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        assert_eq!(result.nb_documents, 1);
+        assert_eq!(result.nb_skipped_missing_primary_key, 1);
+        assert_eq!(result.nb_skipped_invalid_document_id, 1);
+    }
+
     #[test]
     fn complex_json_documents() {
         let path = tempfile::tempdir().unwrap();
@@ -866,6 +1239,44 @@ mod tests {
         drop(rtxn);
     }
 
+    #[test]
+    fn filter_nested_array_of_objects() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("array_of_object.wow") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 0, "name": "kevin", "object": { "key1": "value1", "key2": "value2" } },
+            { "id": 1, "name": "kevina", "array_of_object": [{ "wow": "fine" }] },
+            { "id": 2, "name": "benoit", "array_of_object": [{ "wow": "amazing" }, { "wow": 42 }] }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+
+        // A document matches if any of its array elements matches.
+        let condition = FilterCondition::from_str(&rtxn, &index, "array_of_object.wow = amazing");
+        let documents_ids = condition.unwrap().evaluate(&rtxn, &index).unwrap();
+        assert_eq!(documents_ids.into_iter().collect::<Vec<_>>(), vec![2]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "array_of_object.wow > 40");
+        let documents_ids = condition.unwrap().evaluate(&rtxn, &index).unwrap();
+        assert_eq!(documents_ids.into_iter().collect::<Vec<_>>(), vec![2]);
+
+        let condition = FilterCondition::from_str(&rtxn, &index, "array_of_object.wow = fine");
+        let documents_ids = condition.unwrap().evaluate(&rtxn, &index).unwrap();
+        assert_eq!(documents_ids.into_iter().collect::<Vec<_>>(), vec![1]);
+    }
+
     #[test]
     fn simple_documents_replace() {
         let path = tempfile::tempdir().unwrap();
@@ -1000,4 +1411,119 @@ mod tests {
 
         wtxn.commit().unwrap();
     }
+
+    #[test]
+    fn primary_key_policy_on_addition_and_deletion() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        // The numbers and the strings are accepted, the booleans are not.
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": 1, "name": "kevin" },
+            { "id": "two", "name": "kevina" },
+            { "id": 1.5, "name": "benoit" },
+            { "id": true, "name": "bernard" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.skip_invalid_documents(true);
+        let result = builder.execute(content, |_, _| ()).unwrap();
+        assert_eq!(result.nb_documents, 3);
+        assert_eq!(result.nb_skipped_invalid_document_id, 1);
+        assert!(index.external_documents_ids(&wtxn).unwrap().get("1.5").is_some());
+
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 1).unwrap();
+        assert!(builder.delete_external_id("1").is_some());
+        assert!(builder.delete_external_id("two").is_some());
+        assert_eq!(builder.execute().unwrap(), 2);
+        wtxn.commit().unwrap();
+
+        // The booleans are accepted and the fractional numbers rejected when the policy says so.
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 2);
+        let policy = PrimaryKeyPolicy { allow_booleans: true, reject_fractional_numbers: true };
+        builder.set_primary_key_policy(policy);
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[{ "id": true, "name": "bernard" }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 3);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        let content = &br#"[{ "id": 2.5, "name": "benoit" }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 4);
+        builder.update_format(UpdateFormat::Json);
+        assert!(builder.execute(content, |_, _| ()).is_err());
+
+        // The document indexed with a fractional id before the policy change can be deleted.
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 5).unwrap();
+        assert!(builder.delete_external_id("true").is_some());
+        assert!(builder.delete_external_id("1.5").is_some());
+        assert_eq!(builder.execute().unwrap(), 2);
+        assert_eq!(index.number_of_documents(&wtxn).unwrap(), 0);
+        assert!(index.external_documents_ids(&wtxn).unwrap().get("true").is_none());
+        wtxn.commit().unwrap();
+    }
+
+    #[test]
+    fn typed_csv_columns_with_custom_delimiter() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("price") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content =
+            &b"id;name;price:number;active:boolean\n1;kevin;12.5;true\n2;kevina;;false\n"[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Csv);
+        builder.csv_delimiter(b';');
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        assert_eq!(fields_ids_map.id("price:number"), None);
+        let documents = index.documents(&rtxn, vec![0, 1]).unwrap();
+        let documents: Vec<_> = documents
+            .into_iter()
+            .map(|(_, obkv)| crate::obkv_to_json(&[0, 1, 2, 3], &fields_ids_map, obkv).unwrap())
+            .collect();
+        assert_eq!(documents[0]["price"], serde_json::json!(12.5));
+        assert_eq!(documents[0]["active"], serde_json::json!(true));
+        assert_eq!(documents[1]["price"], serde_json::json!(null));
+        assert_eq!(documents[1]["active"], serde_json::json!(false));
+
+        // The numeric facets are extracted from the typed column.
+        let condition = FilterCondition::from_str(&rtxn, &index, "price > 10").unwrap();
+        assert_eq!(condition.evaluate(&rtxn, &index).unwrap(), RoaringBitmap::from_iter(vec![0]));
+        drop(rtxn);
+
+        // A cell that can't be parsed as the declared type is reported.
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &b"id,price:number\n3,12\n4,twelve\n"[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 2);
+        builder.update_format(UpdateFormat::Csv);
+        match builder.execute(content, |_, _| ()) {
+            Err(crate::Error::UserError(UserError::InvalidCsvValue {
+                row,
+                column,
+                expected,
+                value,
+            })) => {
+                assert_eq!(row, 3);
+                assert_eq!(column, "price");
+                assert_eq!(expected, "number");
+                assert_eq!(value, "twelve");
+            }
+            otherwise => panic!("unexpected result {:?}", otherwise),
+        }
+    }
 }
@@ -9,7 +9,7 @@ use std::time::Instant;
 use grenad::CompressionType;
 use log::info;
 use roaring::RoaringBitmap;
-use serde_json::{Map, Value};
+use serde_json::{Map, Number, Value};
 
 use super::helpers::{
     create_sorter, create_writer, keep_latest_obkv, merge_obkvs, merge_two_obkvs, MergeFn,
@@ -18,7 +18,10 @@ use super::IndexDocumentsMethod;
 use crate::error::{InternalError, UserError};
 use crate::index::db_name;
 use crate::update::{AvailableDocumentsIds, UpdateIndexingStep};
-use crate::{ExternalDocumentsIds, FieldDistribution, FieldId, FieldsIdsMap, Index, Result, BEU32};
+use crate::{
+    ExternalDocumentsIds, FieldDistribution, FieldId, FieldsIdsMap, Index, PrimaryKeyPolicy,
+    Result, BEU32,
+};
 
 const DEFAULT_PRIMARY_KEY_NAME: &str = "id";
 
@@ -31,6 +34,10 @@ pub struct TransformOutput {
     pub replaced_documents_ids: RoaringBitmap,
     pub documents_count: usize,
     pub documents_file: File,
+    /// The number of documents skipped because they were missing the primary key.
+    pub skipped_missing_primary_key: usize,
+    /// The number of documents skipped because their document id was invalid.
+    pub skipped_invalid_document_id: usize,
 }
 
 /// Extract the external ids, deduplicate and compute the new internal documents ids
@@ -49,6 +56,8 @@ pub struct Transform<'t, 'i> {
     pub max_memory: Option<usize>,
     pub index_documents_method: IndexDocumentsMethod,
     pub autogenerate_docids: bool,
+    pub skip_invalid_documents: bool,
+    pub csv_delimiter: u8,
 }
 
 fn is_primary_key(field: impl AsRef<str>) -> bool {
@@ -115,7 +124,7 @@ impl Transform<'_, '_> {
 
         let alternative_name =
             first.and_then(|doc| doc.keys().find(|f| is_primary_key(f)).cloned());
-        let (primary_key_id, primary_key) = compute_primary_key_pair(
+        let (_, primary_key) = compute_primary_key_pair(
             self.index.primary_key(self.rtxn)?,
             &mut fields_ids_map,
             alternative_name,
@@ -132,6 +141,8 @@ impl Transform<'_, '_> {
                 replaced_documents_ids: RoaringBitmap::new(),
                 documents_count: 0,
                 documents_file: tempfile::tempfile()?,
+                skipped_missing_primary_key: 0,
+                skipped_invalid_document_id: 0,
             });
         }
 
@@ -151,10 +162,13 @@ impl Transform<'_, '_> {
             self.max_memory,
         );
 
+        let primary_key_policy = self.index.primary_key_policy(self.rtxn)?;
         let mut json_buffer = Vec::new();
         let mut obkv_buffer = Vec::new();
         let mut uuid_buffer = [0; uuid::adapter::Hyphenated::LENGTH];
         let mut documents_count = 0;
+        let mut skipped_missing_primary_key = 0;
+        let mut skipped_invalid_document_id = 0;
 
         for result in documents {
             let document = result.map_err(UserError::SerdeJson)?;
@@ -165,28 +179,25 @@ impl Transform<'_, '_> {
                 });
             }
 
-            obkv_buffer.clear();
-            let mut writer = obkv::KvWriter::<_, FieldId>::new(&mut obkv_buffer);
-
-            // We prepare the fields ids map with the documents keys.
-            for (key, _value) in &document {
-                fields_ids_map.insert(&key).ok_or(UserError::AttributeLimitReached)?;
-            }
-
             // We retrieve the user id from the document based on the primary key name,
             // if the document id isn't present we generate a uuid.
             let external_id = match document.get(&primary_key) {
-                Some(value) => match value {
-                    Value::String(string) => Cow::Borrowed(string.as_str()),
-                    Value::Number(number) => Cow::Owned(number.to_string()),
-                    content => {
-                        return Err(
-                            UserError::InvalidDocumentId { document_id: content.clone() }.into()
-                        )
+                Some(value) => match extract_external_id(&primary_key_policy, value) {
+                    Ok(external_id) => external_id,
+                    Err(_) if self.skip_invalid_documents => {
+                        skipped_invalid_document_id += 1;
+                        continue;
+                    }
+                    Err(document_id) => {
+                        return Err(UserError::InvalidDocumentId { document_id }.into())
                     }
                 },
                 None => {
                     if !self.autogenerate_docids {
+                        if self.skip_invalid_documents {
+                            skipped_missing_primary_key += 1;
+                            continue;
+                        }
                         return Err(UserError::MissingDocumentId { document }.into());
                     }
                     let uuid = uuid::Uuid::new_v4().to_hyphenated().encode_lower(&mut uuid_buffer);
@@ -194,6 +205,14 @@ impl Transform<'_, '_> {
                 }
             };
 
+            obkv_buffer.clear();
+            let mut writer = obkv::KvWriter::<_, FieldId>::new(&mut obkv_buffer);
+
+            // We prepare the fields ids map with the documents keys.
+            for (key, _value) in &document {
+                fields_ids_map.insert(&key).ok_or(UserError::AttributeLimitReached)?;
+            }
+
             // We iterate in the fields ids ordered.
             for (field_id, name) in fields_ids_map.iter() {
                 json_buffer.clear();
@@ -206,13 +225,6 @@ impl Transform<'_, '_> {
                         .map_err(InternalError::SerdeJson)?;
                     writer.insert(field_id, &json_buffer)?;
                 }
-                // We validate the document id [a-zA-Z0-9\-_].
-                if field_id == primary_key_id && validate_document_id(&external_id).is_none() {
-                    return Err(UserError::InvalidDocumentId {
-                        document_id: Value::from(external_id),
-                    }
-                    .into());
-                }
             }
 
             // We use the extracted/generated user id as the key for this document.
@@ -226,14 +238,17 @@ impl Transform<'_, '_> {
 
         // Now that we have a valid sorter that contains the user id and the obkv we
         // give it to the last transforming function which returns the TransformOutput.
-        self.output_from_sorter(
+        let mut output = self.output_from_sorter(
             sorter,
             primary_key,
             fields_ids_map,
             documents_count,
             external_documents_ids,
             progress_callback,
-        )
+        )?;
+        output.skipped_missing_primary_key = skipped_missing_primary_key;
+        output.skipped_invalid_document_id = skipped_invalid_document_id;
+        Ok(output)
     }
 
     pub fn output_from_csv<R, F>(self, reader: R, progress_callback: F) -> Result<TransformOutput>
@@ -244,28 +259,29 @@ impl Transform<'_, '_> {
         let mut fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
         let external_documents_ids = self.index.external_documents_ids(self.rtxn).unwrap();
 
-        let mut csv = csv::Reader::from_reader(reader);
+        let mut csv = csv::ReaderBuilder::new().delimiter(self.csv_delimiter).from_reader(reader);
         let headers = csv.headers().map_err(UserError::Csv)?;
+        let headers: Vec<_> = headers.iter().map(parse_csv_header).collect();
 
         let mut fields_ids = Vec::new();
         // Generate the new fields ids based on the current fields ids and this CSV headers.
-        for (i, header) in headers.iter().enumerate() {
+        for (i, (header, kind)) in headers.iter().enumerate() {
             let id = fields_ids_map.insert(header).ok_or(UserError::AttributeLimitReached)?;
-            fields_ids.push((id, i));
+            fields_ids.push((id, i, *kind));
         }
 
         // Extract the position of the primary key in the current headers, None if not found.
         let primary_key_pos = match self.index.primary_key(self.rtxn)? {
             Some(primary_key) => {
                 // The primary key is known so we must find the position in the CSV headers.
-                headers.iter().position(|h| h == primary_key)
+                headers.iter().position(|(h, _)| *h == primary_key)
             }
-            None => headers.iter().position(is_primary_key),
+            None => headers.iter().position(|(h, _)| is_primary_key(h)),
         };
 
         // Returns the field id in the fields ids map, create an "id" field
         // in case it is not in the current headers.
-        let alternative_name = primary_key_pos.map(|pos| headers[pos].to_string());
+        let alternative_name = primary_key_pos.map(|pos| headers[pos].0.to_string());
         let (primary_key_id, primary_key_name) = compute_primary_key_pair(
             self.index.primary_key(self.rtxn)?,
             &mut fields_ids_map,
@@ -275,12 +291,13 @@ impl Transform<'_, '_> {
 
         // The primary key field is not present in the header, so we need to create it.
         if primary_key_pos.is_none() {
-            fields_ids.push((primary_key_id, usize::max_value()));
+            fields_ids.push((primary_key_id, usize::max_value(), CsvFieldType::String));
         }
 
         // We sort the fields ids by the fields ids map id, this way we are sure to iterate over
         // the records fields in the fields ids map order and correctly generate the obkv.
-        fields_ids.sort_unstable_by_key(|(field_id, _)| *field_id);
+        fields_ids.sort_unstable_by_key(|(field_id, _, _)| *field_id);
+        let columns: Vec<_> = headers.iter().map(|(h, _)| h.to_string()).collect();
 
         // We initialize the sorter with the user indexing settings.
         let mut sorter = create_sorter(
@@ -297,6 +314,8 @@ impl Transform<'_, '_> {
         let mut obkv_buffer = Vec::new();
         let mut uuid_buffer = [0; uuid::adapter::Hyphenated::LENGTH];
         let mut documents_count = 0;
+        let mut skipped_missing_primary_key = 0;
+        let mut skipped_invalid_document_id = 0;
 
         let mut record = csv::StringRecord::new();
         while csv.read_record(&mut record).map_err(UserError::Csv)? {
@@ -316,6 +335,15 @@ impl Transform<'_, '_> {
                     // We validate the document id [a-zA-Z0-9\-_].
                     match validate_document_id(&external_id) {
                         Some(valid) => valid,
+                        // An empty cell is considered as a missing primary key.
+                        None if self.skip_invalid_documents && external_id.trim().is_empty() => {
+                            skipped_missing_primary_key += 1;
+                            continue;
+                        }
+                        None if self.skip_invalid_documents => {
+                            skipped_invalid_document_id += 1;
+                            continue;
+                        }
                         None => {
                             return Err(UserError::InvalidDocumentId {
                                 document_id: Value::from(external_id),
@@ -329,16 +357,23 @@ impl Transform<'_, '_> {
 
             // When the primary_key_field_id is found in the fields ids list
             // we return the generated document id instead of the record field.
-            let iter = fields_ids.iter().map(|(fi, i)| {
+            let iter = fields_ids.iter().map(|(fi, i, kind)| {
                 let field = if *fi == primary_key_id { external_id } else { &record[*i] };
-                (fi, field)
+                (fi, i, kind, field)
             });
 
             // We retrieve the field id based on the fields ids map fields ids order.
-            for (field_id, field) in iter {
-                // We serialize the attribute values as JSON strings.
+            for (field_id, i, kind, field) in iter {
+                // We convert the attribute values into the JSON type declared in the header.
+                let value =
+                    csv_field_value(field, *kind).ok_or_else(|| UserError::InvalidCsvValue {
+                        row: record.position().map_or(0, |p| p.line()),
+                        column: columns[*i].clone(),
+                        expected: kind.name(),
+                        value: field.to_string(),
+                    })?;
                 json_buffer.clear();
-                serde_json::to_writer(&mut json_buffer, &field)
+                serde_json::to_writer(&mut json_buffer, &value)
                     .map_err(InternalError::SerdeJson)?;
                 writer.insert(*field_id, &json_buffer)?;
             }
@@ -354,14 +389,17 @@ impl Transform<'_, '_> {
 
         // Now that we have a valid sorter that contains the user id and the obkv we
         // give it to the last transforming function which returns the TransformOutput.
-        self.output_from_sorter(
+        let mut output = self.output_from_sorter(
             sorter,
             primary_key_name,
             fields_ids_map,
             documents_count,
             external_documents_ids,
             progress_callback,
-        )
+        )?;
+        output.skipped_missing_primary_key = skipped_missing_primary_key;
+        output.skipped_invalid_document_id = skipped_invalid_document_id;
+        Ok(output)
     }
 
     /// Generate the `TransformOutput` based on the given sorter that can be generated from any
@@ -507,21 +545,28 @@ impl Transform<'_, '_> {
             replaced_documents_ids,
             documents_count,
             documents_file,
+            skipped_missing_primary_key: 0,
+            skipped_invalid_document_id: 0,
         })
     }
 
     /// Returns a `TransformOutput` with a file that contains the documents of the index
     /// with the attributes reordered accordingly to the `FieldsIdsMap` given as argument.
+    ///
+    /// Only the documents of the index listed in `documents_ids` are remapped, they are all
+    /// the documents to index when the output is given to [`IndexDocuments::execute_raw`].
+    ///
+    /// [`IndexDocuments::execute_raw`]: super::IndexDocuments::execute_raw
     // TODO this can be done in parallel by using the rayon `ThreadPool`.
     pub fn remap_index_documents(
         self,
         primary_key: String,
+        documents_ids: RoaringBitmap,
         old_fields_ids_map: FieldsIdsMap,
         new_fields_ids_map: FieldsIdsMap,
     ) -> Result<TransformOutput> {
         let field_distribution = self.index.field_distribution(self.rtxn)?;
         let external_documents_ids = self.index.external_documents_ids(self.rtxn)?;
-        let documents_ids = self.index.documents_ids(self.rtxn)?;
         let documents_count = documents_ids.len() as usize;
 
         // We create a final writer to write the new documents in order from the sorter.
@@ -530,9 +575,10 @@ impl Transform<'_, '_> {
             create_writer(self.chunk_compression_type, self.chunk_compression_level, file)?;
 
         let mut obkv_buffer = Vec::new();
-        for result in self.index.documents.iter(self.rtxn)? {
-            let (docid, obkv) = result?;
-            let docid = docid.get();
+        for docid in &documents_ids {
+            let obkv = self.index.documents.get(self.rtxn, &BEU32::new(docid))?.ok_or(
+                InternalError::DatabaseMissingEntry { db_name: db_name::DOCUMENTS, key: None },
+            )?;
 
             obkv_buffer.clear();
             let mut obkv_writer = obkv::KvWriter::<_, FieldId>::new(&mut obkv_buffer);
@@ -562,10 +608,56 @@ impl Transform<'_, '_> {
             replaced_documents_ids: RoaringBitmap::default(),
             documents_count,
             documents_file,
+            skipped_missing_primary_key: 0,
+            skipped_invalid_document_id: 0,
         })
     }
 }
 
+/// The JSON type of the values of a CSV column, declared with a suffix of its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsvFieldType {
+    String,
+    Number,
+    Boolean,
+}
+
+impl CsvFieldType {
+    fn name(&self) -> &'static str {
+        match self {
+            CsvFieldType::String => "string",
+            CsvFieldType::Number => "number",
+            CsvFieldType::Boolean => "boolean",
+        }
+    }
+}
+
+/// Splits a CSV header into the field name and the type of the column,
+/// a header without a known type suffix is a string column.
+fn parse_csv_header(header: &str) -> (&str, CsvFieldType) {
+    match header.rsplit_once(':') {
+        Some((name, "string")) => (name, CsvFieldType::String),
+        Some((name, "number")) => (name, CsvFieldType::Number),
+        Some((name, "boolean")) => (name, CsvFieldType::Boolean),
+        _otherwise => (header, CsvFieldType::String),
+    }
+}
+
+/// Converts a CSV cell into a JSON value of the given type, returns `None` if it can't
+/// be parsed as this type. The empty cells of the typed columns are converted into `null`.
+fn csv_field_value(field: &str, kind: CsvFieldType) -> Option<Value> {
+    let trimmed = field.trim();
+    match kind {
+        CsvFieldType::String => Some(Value::from(field)),
+        CsvFieldType::Number | CsvFieldType::Boolean if trimmed.is_empty() => Some(Value::Null),
+        CsvFieldType::Number => match trimmed.parse::<i64>() {
+            Ok(integer) => Some(Value::from(integer)),
+            Err(_) => trimmed.parse::<f64>().ok().and_then(Number::from_f64).map(Value::Number),
+        },
+        CsvFieldType::Boolean => trimmed.parse::<bool>().ok().map(Value::Bool),
+    }
+}
+
 /// Given an optional primary key and an optional alternative name, returns the (field_id, attr_name)
 /// for the primary key according to the following rules:
 /// - if primary_key is `Some`, returns the id and the name, else
@@ -602,6 +694,25 @@ fn compute_primary_key_pair(
     }
 }
 
+/// Converts the value of the primary key of a document into its external id, following the
+/// primary key policy, and validates it [a-zA-Z0-9\-_].
+///
+/// The numbers are converted into their canonical representation, e.g. `1.5` or `-42`, which
+/// is not validated: the `.` of the fractional numbers the policy accepts must be kept.
+///
+/// Returns the invalid document id as an error.
+pub(crate) fn extract_external_id<'a>(
+    primary_key_policy: &PrimaryKeyPolicy,
+    value: &'a Value,
+) -> StdResult<Cow<'a, str>, Value> {
+    let external_id = primary_key_policy.document_id(value).ok_or_else(|| value.clone())?;
+    if value.is_number() || validate_document_id(&external_id).is_some() {
+        Ok(external_id)
+    } else {
+        Err(Value::from(external_id))
+    }
+}
+
 fn validate_document_id(document_id: &str) -> Option<&str> {
     let document_id = document_id.trim();
     Some(document_id).filter(|id| {
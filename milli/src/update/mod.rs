@@ -1,13 +1,16 @@
 pub use self::available_documents_ids::AvailableDocumentsIds;
 pub use self::clear_documents::ClearDocuments;
-pub use self::delete_documents::DeleteDocuments;
+pub use self::delete_documents::{DeleteDocuments, DocumentDeletionResult};
 pub use self::facets::Facets;
 pub use self::index_documents::{
-    DocumentAdditionResult, IndexDocuments, IndexDocumentsMethod, UpdateFormat,
+    create_sorter, create_writer, sorter_into_lmdb_database, write_into_lmdb_database,
+    writer_into_reader, DerivedDatabaseExtractor, DocumentAdditionResult, IndexDocuments,
+    IndexDocumentsMethod, MergeFn, UpdateFormat, WriteMethod,
 };
 pub use self::settings::{Setting, Settings};
 pub use self::update_builder::UpdateBuilder;
 pub use self::update_step::UpdateIndexingStep;
+pub use self::upsert_document::upsert_document;
 pub use self::word_prefix_docids::WordPrefixDocids;
 pub use self::word_prefix_pair_proximity_docids::WordPrefixPairProximityDocids;
 pub use self::words_level_positions::WordsLevelPositions;
@@ -21,6 +24,7 @@ mod index_documents;
 mod settings;
 mod update_builder;
 mod update_step;
+mod upsert_document;
 mod word_prefix_docids;
 mod word_prefix_pair_proximity_docids;
 mod words_level_positions;
@@ -1,4 +1,5 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::iter;
 use std::result::Result as StdResult;
 
 use chrono::Utc;
@@ -12,7 +13,7 @@ use crate::criterion::Criterion;
 use crate::error::UserError;
 use crate::update::index_documents::{IndexDocumentsMethod, Transform};
 use crate::update::{ClearDocuments, IndexDocuments, UpdateIndexingStep};
-use crate::{FieldsIdsMap, Index, Result};
+use crate::{FieldsIdsMap, Index, PrimaryKeyPolicy, Result};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Setting<T> {
@@ -77,9 +78,16 @@ pub struct Settings<'a, 't, 'u, 'i> {
     sortable_fields: Setting<HashSet<String>>,
     criteria: Setting<Vec<String>>,
     stop_words: Setting<BTreeSet<String>>,
+    language_stop_words: HashMap<String, Setting<BTreeSet<String>>>,
     distinct_field: Setting<String>,
     synonyms: Setting<HashMap<String, Vec<String>>>,
     primary_key: Setting<String>,
+    max_proximity_distance: Setting<u8>,
+    max_facet_value_length: Setting<usize>,
+    date_fields: Setting<HashSet<String>>,
+    exactness_prefix_tier: Setting<bool>,
+    max_ngram: Setting<Option<usize>>,
+    primary_key_policy: Setting<PrimaryKeyPolicy>,
 }
 
 impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
@@ -104,9 +112,16 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
             sortable_fields: Setting::NotSet,
             criteria: Setting::NotSet,
             stop_words: Setting::NotSet,
+            language_stop_words: HashMap::new(),
             distinct_field: Setting::NotSet,
             synonyms: Setting::NotSet,
             primary_key: Setting::NotSet,
+            max_proximity_distance: Setting::NotSet,
+            max_facet_value_length: Setting::NotSet,
+            date_fields: Setting::NotSet,
+            exactness_prefix_tier: Setting::NotSet,
+            max_ngram: Setting::NotSet,
+            primary_key_policy: Setting::NotSet,
             update_id,
         }
     }
@@ -160,6 +175,18 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
             if stop_words.is_empty() { Setting::Reset } else { Setting::Set(stop_words) }
     }
 
+    pub fn reset_stop_words_for_language(&mut self, language: String) {
+        self.language_stop_words.insert(language, Setting::Reset);
+    }
+
+    /// Registers a list of stop words that are only used when indexing or searching
+    /// with the given language hint, in place of the global stop words.
+    pub fn set_stop_words_for_language(&mut self, language: String, stop_words: Vec<String>) {
+        let stop_words: BTreeSet<_> = stop_words.into_iter().collect();
+        let setting = if stop_words.is_empty() { Setting::Reset } else { Setting::Set(stop_words) };
+        self.language_stop_words.insert(language, setting);
+    }
+
     pub fn reset_distinct_field(&mut self) {
         self.distinct_field = Setting::Reset;
     }
@@ -184,6 +211,74 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         self.primary_key = Setting::Set(primary_key);
     }
 
+    pub fn reset_primary_key_policy(&mut self) {
+        self.primary_key_policy = Setting::Reset;
+    }
+
+    /// Defines which primary key values are accepted as document ids when adding documents,
+    /// the already indexed documents can still be deleted if the policy is made stricter.
+    pub fn set_primary_key_policy(&mut self, policy: PrimaryKeyPolicy) {
+        self.primary_key_policy = Setting::Set(policy);
+    }
+
+    pub fn reset_max_proximity_distance(&mut self) {
+        self.max_proximity_distance = Setting::Reset;
+    }
+
+    /// Word pairs farther apart than this distance are not recorded in the proximity
+    /// database, which shrinks it at the cost of the proximity precision of distant words.
+    /// The default, and maximum, distance is 7.
+    pub fn set_max_proximity_distance(&mut self, distance: u8) {
+        self.max_proximity_distance = Setting::Set(distance);
+    }
+
+    pub fn reset_max_facet_value_length(&mut self) {
+        self.max_facet_value_length = Setting::Reset;
+    }
+
+    /// The facet strings longer than this number of characters are truncated when indexed,
+    /// the filters on the longer values must therefore only use their truncated form.
+    pub fn set_max_facet_value_length(&mut self, length: usize) {
+        self.max_facet_value_length = Setting::Set(length);
+    }
+
+    pub fn reset_date_fields(&mut self) {
+        self.date_fields = Setting::Reset;
+    }
+
+    /// The string values of these faceted fields are parsed as dates and indexed as the
+    /// number of milliseconds since the epoch, the filters on these fields accept dates.
+    /// The documents with a value that is not a valid date are rejected.
+    pub fn set_date_fields(&mut self, names: HashSet<String>) {
+        self.date_fields = Setting::Set(names);
+    }
+
+    pub fn reset_ngram(&mut self) {
+        self.max_ngram = Setting::Reset;
+    }
+
+    /// When enabled, up to `max_n` consecutive words of the documents are also concatenated
+    /// and indexed as single words, e.g. `data base` is also indexed as `database`.
+    ///
+    /// This setting only concerns the indexing, the search always tries to concatenate up to
+    /// three consecutive query words, whatever the `max_n` is and even when this is disabled.
+    ///
+    /// Every combination of consecutive words becomes a new word in the databases, the
+    /// words and proximity databases therefore grow roughly `max_n - 1` times bigger.
+    pub fn set_ngram(&mut self, enabled: bool, max_n: usize) {
+        self.max_ngram = Setting::Set(if enabled { Some(max_n) } else { None });
+    }
+
+    pub fn reset_exactness_prefix_tier(&mut self) {
+        self.exactness_prefix_tier = Setting::Reset;
+    }
+
+    /// When enabled, the exactness criterion ranks the documents that contain the query words
+    /// as prefixes after the ones that contain them exactly but before the ones with typos.
+    pub fn set_exactness_prefix_tier(&mut self, prefix_tier: bool) {
+        self.exactness_prefix_tier = Setting::Set(prefix_tier);
+    }
+
     fn reindex<F>(&mut self, cb: &F, old_fields_ids_map: FieldsIdsMap) -> Result<()>
     where
         F: Fn(UpdateIndexingStep, u64) + Sync,
@@ -197,43 +292,66 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
             return Ok(());
         }
 
-        let transform = Transform {
-            rtxn: &self.wtxn,
-            index: self.index,
-            log_every_n: self.log_every_n,
-            chunk_compression_type: self.chunk_compression_type,
-            chunk_compression_level: self.chunk_compression_level,
-            max_nb_chunks: self.max_nb_chunks,
-            max_memory: self.max_memory,
-            index_documents_method: IndexDocumentsMethod::ReplaceDocuments,
-            autogenerate_docids: false,
-        };
-
         // There already has been a document addition, the primary key should be set by now.
         let primary_key =
-            self.index.primary_key(&self.wtxn)?.ok_or(UserError::MissingPrimaryKey)?;
+            self.index.primary_key(&self.wtxn)?.ok_or(UserError::MissingPrimaryKey)?.to_string();
+
+        // The documents are indexed again with the language hint they have been indexed with,
+        // for the stop words of their language to still be applied.
+        let languages = self.index.languages_documents_ids(self.wtxn)?;
+        let mut without_language = self.index.documents_ids(self.wtxn)?;
+        for docids in languages.values() {
+            without_language -= docids;
+        }
+        let groups = iter::once((None, without_language))
+            .chain(languages.into_iter().map(|(language, docids)| (Some(language), docids)))
+            .filter(|(_, docids)| !docids.is_empty());
 
         // We remap the documents fields based on the new `FieldsIdsMap`.
-        let output = transform.remap_index_documents(
-            primary_key.to_string(),
-            old_fields_ids_map,
-            fields_ids_map.clone(),
-        )?;
+        let mut outputs = Vec::new();
+        for (language, documents_ids) in groups {
+            let transform = Transform {
+                rtxn: &self.wtxn,
+                index: self.index,
+                log_every_n: self.log_every_n,
+                chunk_compression_type: self.chunk_compression_type,
+                chunk_compression_level: self.chunk_compression_level,
+                max_nb_chunks: self.max_nb_chunks,
+                max_memory: self.max_memory,
+                index_documents_method: IndexDocumentsMethod::ReplaceDocuments,
+                autogenerate_docids: false,
+                skip_invalid_documents: false,
+                csv_delimiter: b',',
+            };
+
+            let output = transform.remap_index_documents(
+                primary_key.clone(),
+                documents_ids,
+                old_fields_ids_map.clone(),
+                fields_ids_map.clone(),
+            )?;
+            outputs.push((language, output));
+        }
 
         // We clear the full database (words-fst, documents ids and documents content).
         ClearDocuments::new(self.wtxn, self.index, self.update_id).execute()?;
 
-        // We index the generated `TransformOutput` which must contain
+        // We index the generated `TransformOutput`s which must contain
         // all the documents with fields in the newly defined searchable order.
-        let mut indexing_builder = IndexDocuments::new(self.wtxn, self.index, self.update_id);
-        indexing_builder.log_every_n = self.log_every_n;
-        indexing_builder.max_nb_chunks = self.max_nb_chunks;
-        indexing_builder.max_memory = self.max_memory;
-        indexing_builder.documents_chunk_size = self.documents_chunk_size;
-        indexing_builder.chunk_compression_type = self.chunk_compression_type;
-        indexing_builder.chunk_compression_level = self.chunk_compression_level;
-        indexing_builder.thread_pool = self.thread_pool;
-        indexing_builder.execute_raw(output, &cb)?;
+        for (language, output) in outputs {
+            let mut indexing_builder = IndexDocuments::new(self.wtxn, self.index, self.update_id);
+            indexing_builder.log_every_n = self.log_every_n;
+            indexing_builder.max_nb_chunks = self.max_nb_chunks;
+            indexing_builder.max_memory = self.max_memory;
+            indexing_builder.documents_chunk_size = self.documents_chunk_size;
+            indexing_builder.chunk_compression_type = self.chunk_compression_type;
+            indexing_builder.chunk_compression_level = self.chunk_compression_level;
+            indexing_builder.thread_pool = self.thread_pool;
+            if let Some(language) = language {
+                indexing_builder.language(language);
+            }
+            indexing_builder.execute_raw(output, &cb)?;
+        }
 
         Ok(())
     }
@@ -324,6 +442,82 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         }
     }
 
+    fn update_max_proximity_distance(&mut self) -> Result<bool> {
+        let current = self.index.max_proximity_distance(self.wtxn)?;
+        match self.max_proximity_distance {
+            Setting::Set(distance) => {
+                self.index.put_max_proximity_distance(self.wtxn, distance)?;
+                Ok(current != distance)
+            }
+            Setting::Reset => {
+                self.index.delete_max_proximity_distance(self.wtxn)?;
+                Ok(current != self.index.max_proximity_distance(self.wtxn)?)
+            }
+            Setting::NotSet => Ok(false),
+        }
+    }
+
+    fn update_max_facet_value_length(&mut self) -> Result<bool> {
+        let current = self.index.max_facet_value_length(self.wtxn)?;
+        match self.max_facet_value_length {
+            Setting::Set(length) => {
+                self.index.put_max_facet_value_length(self.wtxn, length)?;
+                Ok(current != Some(length))
+            }
+            Setting::Reset => Ok(self.index.delete_max_facet_value_length(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
+
+    fn update_date_fields(&mut self) -> Result<bool> {
+        let current = self.index.date_fields(self.wtxn)?;
+        match self.date_fields {
+            Setting::Set(ref fields) => {
+                self.index.put_date_fields(self.wtxn, fields)?;
+                Ok(&current != fields)
+            }
+            Setting::Reset => Ok(self.index.delete_date_fields(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
+
+    fn update_max_ngram(&mut self) -> Result<bool> {
+        let current = self.index.max_ngram(self.wtxn)?;
+        match self.max_ngram {
+            Setting::Set(Some(max_ngram)) => {
+                self.index.put_max_ngram(self.wtxn, max_ngram)?;
+                Ok(current != Some(max_ngram))
+            }
+            Setting::Set(None) | Setting::Reset => Ok(self.index.delete_max_ngram(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
+
+    fn update_language_stop_words(&mut self) -> Result<bool> {
+        let mut updated = false;
+        for (language, setting) in &self.language_stop_words {
+            match setting {
+                Setting::Set(stop_words) => {
+                    let current = self.index.language_stop_words(self.wtxn, language)?;
+                    let fst = fst::Set::from_iter(stop_words)?;
+
+                    // Does the new FST differ from the previous one?
+                    if current.map_or(true, |current| {
+                        current.as_fst().as_bytes() != fst.as_fst().as_bytes()
+                    }) {
+                        self.index.put_language_stop_words(self.wtxn, language, &fst)?;
+                        updated = true;
+                    }
+                }
+                Setting::Reset => {
+                    updated |= self.index.delete_language_stop_words(self.wtxn, language)?;
+                }
+                Setting::NotSet => (),
+            }
+        }
+        Ok(updated)
+    }
+
     fn update_synonyms(&mut self) -> Result<bool> {
         match self.synonyms {
             Setting::Set(ref synonyms) => {
@@ -433,6 +627,32 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         Ok(())
     }
 
+    fn update_exactness_prefix_tier(&mut self) -> Result<()> {
+        match self.exactness_prefix_tier {
+            Setting::Set(prefix_tier) => {
+                self.index.put_exactness_prefix_tier(self.wtxn, prefix_tier)?;
+            }
+            Setting::Reset => {
+                self.index.delete_exactness_prefix_tier(self.wtxn)?;
+            }
+            Setting::NotSet => (),
+        }
+        Ok(())
+    }
+
+    fn update_primary_key_policy(&mut self) -> Result<()> {
+        match self.primary_key_policy {
+            Setting::Set(ref policy) => {
+                self.index.put_primary_key_policy(self.wtxn, policy)?;
+            }
+            Setting::Reset => {
+                self.index.delete_primary_key_policy(self.wtxn)?;
+            }
+            Setting::NotSet => (),
+        }
+        Ok(())
+    }
+
     fn update_primary_key(&mut self) -> Result<()> {
         match self.primary_key {
             Setting::Set(ref primary_key) => {
@@ -472,19 +692,43 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         self.update_sortable()?;
         self.update_distinct_field()?;
         self.update_criteria()?;
+        self.update_exactness_prefix_tier()?;
         self.update_primary_key()?;
+        self.update_primary_key_policy()?;
 
         // If there is new faceted fields we indicate that we must reindex as we must
         // index new fields as facets. It means that the distinct attribute,
-        // an Asc/Desc criterion or a filtered attribute as be added or removed.
+        // an Asc/Desc criterion or a filtered attribute as be added.
         let new_faceted_fields = self.index.faceted_fields(&self.wtxn)?;
-        let faceted_updated = old_faceted_fields != new_faceted_fields;
+        let faceted_updated = !new_faceted_fields.is_subset(&old_faceted_fields);
+
+        // The fields that are no more faceted only have their facet values removed,
+        // there is no need to reindex the documents for that.
+        for name in old_faceted_fields.difference(&new_faceted_fields) {
+            if let Some(field_id) = old_fields_ids_map.id(name) {
+                self.index.remove_field_facets(self.wtxn, field_id)?;
+            }
+        }
 
         let stop_words_updated = self.update_stop_words()?;
+        let language_stop_words_updated = self.update_language_stop_words()?;
         let synonyms_updated = self.update_synonyms()?;
         let searchable_updated = self.update_searchable()?;
-
-        if stop_words_updated || faceted_updated || synonyms_updated || searchable_updated {
+        let max_proximity_distance_updated = self.update_max_proximity_distance()?;
+        let max_facet_value_length_updated = self.update_max_facet_value_length()?;
+        let date_fields_updated = self.update_date_fields()?;
+        let max_ngram_updated = self.update_max_ngram()?;
+
+        if stop_words_updated
+            || language_stop_words_updated
+            || max_proximity_distance_updated
+            || max_facet_value_length_updated
+            || date_fields_updated
+            || max_ngram_updated
+            || faceted_updated
+            || synonyms_updated
+            || searchable_updated
+        {
             self.reindex(&progress_callback, old_fields_ids_map)?;
         }
 
@@ -497,11 +741,11 @@ mod tests {
     use big_s::S;
     use heed::types::ByteSlice;
     use heed::EnvOpenOptions;
-    use maplit::{btreeset, hashmap, hashset};
+    use maplit::{btreemap, btreeset, hashmap, hashset};
 
     use super::*;
     use crate::error::Error;
-    use crate::update::{IndexDocuments, UpdateFormat};
+    use crate::update::{DeleteDocuments, IndexDocuments, UpdateFormat};
     use crate::{Criterion, FilterCondition, SearchResult};
 
     #[test]
@@ -664,6 +908,215 @@ mod tests {
         assert_eq!(fields_ids, None);
     }
 
+    #[test]
+    fn max_facet_value_length() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("label") });
+        builder.set_max_facet_value_length(10);
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "label": "New York City Of Dreams" },
+            { "id": 2, "label": "Paris" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_facet_value_length(&rtxn).unwrap(), Some(10));
+        let distribution = index.facets_distribution(&rtxn).execute().unwrap();
+        assert_eq!(distribution["label"], btreemap! { S("New York C") => 1, S("Paris") => 1 });
+
+        // Only the truncated value can be matched.
+        let condition = FilterCondition::from_str(&rtxn, &index, "label = 'new york c'").unwrap();
+        assert_eq!(condition.evaluate(&rtxn, &index).unwrap().len(), 1);
+        let condition =
+            FilterCondition::from_str(&rtxn, &index, "label = 'new york city of dreams'").unwrap();
+        assert!(condition.evaluate(&rtxn, &index).unwrap().is_empty());
+        drop(rtxn);
+
+        // Resetting the setting reindexes the complete values.
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 1);
+        builder.reset_max_facet_value_length();
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_facet_value_length(&rtxn).unwrap(), None);
+        let distribution = index.facets_distribution(&rtxn).execute().unwrap();
+        assert_eq!(
+            distribution["label"],
+            btreemap! { S("New York City Of Dreams") => 1, S("Paris") => 1 }
+        );
+    }
+
+    #[test]
+    fn set_and_reset_ngram() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_ngram(true, 2);
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "text": "a data base system" },
+            { "id": 2, "text": "a data. base system" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // The words separated by a hard separator are not concatenated.
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_ngram(&rtxn).unwrap(), Some(2));
+        assert!(index.words_fst(&rtxn).unwrap().contains("database"));
+        assert!(!index.words_fst(&rtxn).unwrap().contains("databasesystem"));
+        let result = index.search(&rtxn).query("database").exact_only(true).execute().unwrap();
+        assert_eq!(result.documents_ids, vec![0]);
+        drop(rtxn);
+
+        // Disabling the ngrams reindexes the documents without the concatenated words.
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 1);
+        builder.set_ngram(false, 2);
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_ngram(&rtxn).unwrap(), None);
+        assert!(!index.words_fst(&rtxn).unwrap().contains("database"));
+        let result = index.search(&rtxn).query("database").exact_only(true).execute().unwrap();
+        assert!(result.documents_ids.is_empty());
+    }
+
+    #[test]
+    fn ngram_does_not_limit_the_query_concatenations() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_ngram(true, 2);
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[{ "id": 1, "text": "the databasesystem" }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // The three query words are still concatenated even if only two
+        // consecutive words of the documents are concatenated when indexing.
+        let rtxn = index.read_txn().unwrap();
+        let result = index.search(&rtxn).query("data base system").execute().unwrap();
+        assert_eq!(result.documents_ids, vec![0]);
+    }
+
+    #[test]
+    fn set_and_reset_max_proximity_distance() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[
+            { "id": 1, "text": "the quick brown fox jumps over the lazy dog" },
+            { "id": 2, "text": "a journey of a thousand miles begins with a single step" }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_proximity_distance(&rtxn).unwrap(), 7);
+        let default_count = index.word_pair_proximity_docids.iter(&rtxn).unwrap().count();
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 1);
+        builder.set_max_proximity_distance(1);
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_proximity_distance(&rtxn).unwrap(), 1);
+        let mut count = 0;
+        for result in index.word_pair_proximity_docids.iter(&rtxn).unwrap() {
+            let ((_, _, prox), _) = result.unwrap();
+            assert_eq!(prox, 1);
+            count += 1;
+        }
+        assert!(count > 0);
+        assert!(count < default_count);
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 2);
+        builder.reset_max_proximity_distance();
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_proximity_distance(&rtxn).unwrap(), 7);
+        let count = index.word_pair_proximity_docids.iter(&rtxn).unwrap().count();
+        assert_eq!(count, default_count);
+    }
+
+    #[test]
+    fn set_and_reset_filterable_and_sortable_fields() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        // By default no field is filterable nor sortable.
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.filterable_fields(&rtxn).unwrap().is_empty());
+        assert!(index.sortable_fields(&rtxn).unwrap().is_empty());
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("age"), S("name") });
+        builder.set_sortable_fields(hashset! { S("age") });
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.filterable_fields(&rtxn).unwrap(), hashset! { S("age"), S("name") });
+        assert_eq!(index.sortable_fields(&rtxn).unwrap(), hashset! { S("age") });
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 1);
+        builder.reset_filterable_fields();
+        builder.reset_sortable_fields();
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.filterable_fields(&rtxn).unwrap().is_empty());
+        assert!(index.sortable_fields(&rtxn).unwrap().is_empty());
+    }
+
     #[test]
     fn set_filterable_fields() {
         let path = tempfile::tempdir().unwrap();
@@ -731,6 +1184,66 @@ mod tests {
         assert_eq!(count, 4);
     }
 
+    #[test]
+    fn remove_filterable_field_without_reindexing() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("color"), S("age") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[
+            { "id": 1, "name": "kevin", "color": "red", "age": 23 },
+            { "id": 2, "name": "kevina", "color": "blue", "age": 21 },
+            { "id": 3, "name": "benoit", "color": "red", "age": 34 }
+        ]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // The color is no more filterable, the documents must not be reindexed.
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 2);
+        builder.set_filterable_fields(hashset! { S("age") });
+        let reindexed = std::sync::atomic::AtomicBool::new(false);
+        builder
+            .execute(|_, _| reindexed.store(true, std::sync::atomic::Ordering::Relaxed))
+            .unwrap();
+        wtxn.commit().unwrap();
+        assert!(!reindexed.into_inner());
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let color = fields_ids_map.id("color").unwrap();
+        let age = fields_ids_map.id("age").unwrap();
+
+        let databases = [
+            index.facet_id_f64_docids.remap_types::<ByteSlice, ByteSlice>(),
+            index.facet_id_string_docids.remap_types::<ByteSlice, ByteSlice>(),
+            index.field_id_docid_facet_f64s.remap_types::<ByteSlice, ByteSlice>(),
+            index.field_id_docid_facet_strings.remap_types::<ByteSlice, ByteSlice>(),
+        ];
+        for database in &databases {
+            assert_eq!(database.prefix_iter(&rtxn, &color.to_be_bytes()).unwrap().count(), 0);
+        }
+        assert!(index.string_faceted_documents_ids(&rtxn, color).unwrap().is_empty());
+
+        // The other facets are kept.
+        assert_eq!(index.number_faceted_documents_ids(&rtxn, age).unwrap().len(), 3);
+        let condition = FilterCondition::from_str(&rtxn, &index, "age = 21").unwrap();
+        assert_eq!(condition.evaluate(&rtxn, &index).unwrap().len(), 1);
+
+        // The field is still searchable.
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("blue").execute().unwrap();
+        assert_eq!(documents_ids, vec![1]);
+    }
+
     #[test]
     fn set_asc_desc_field() {
         let path = tempfile::tempdir().unwrap();
@@ -906,6 +1419,121 @@ mod tests {
         assert_eq!(result.documents_ids.len(), 1); // there is one benoit in our data
     }
 
+    #[test]
+    fn set_stop_words_for_languages() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &b"name,maxim\nkevin,the cat is here\nkevina,le chat est la\n"[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.enable_autogenerate_docids();
+        builder.update_format(UpdateFormat::Csv);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_stop_words_for_language(S("en"), vec![S("the"), S("is")]);
+        builder.set_stop_words_for_language(S("fr"), vec![S("le"), S("est")]);
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let english = index.language_stop_words(&rtxn, "en").unwrap().unwrap();
+        assert!(english.contains("the"));
+        assert!(!english.contains("le"));
+        let french = index.language_stop_words(&rtxn, "fr").unwrap().unwrap();
+        assert!(french.contains("le"));
+        assert!(!french.contains("the"));
+        // The global stop words are not impacted.
+        assert!(index.stop_words(&rtxn).unwrap().is_none());
+
+        // An english stop word is ignored when searching in english,
+        // we get a placeholder search returning all the documents.
+        let result = index.search(&rtxn).query("the ").language("en").execute().unwrap();
+        assert_eq!(result.documents_ids.len(), 2);
+        // But not when searching in french.
+        let result = index.search(&rtxn).query("the ").language("fr").execute().unwrap();
+        assert_eq!(result.documents_ids.len(), 1);
+
+        let result = index.search(&rtxn).query("le ").language("fr").execute().unwrap();
+        assert_eq!(result.documents_ids.len(), 2);
+        let result = index.search(&rtxn).query("le ").language("en").execute().unwrap();
+        assert_eq!(result.documents_ids.len(), 1);
+
+        // Without any language hint, no stop words are applied.
+        let result = index.search(&rtxn).query("the ").execute().unwrap();
+        assert_eq!(result.documents_ids.len(), 1);
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.reset_stop_words_for_language(S("en"));
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.language_stop_words(&rtxn, "en").unwrap().is_none());
+        assert!(index.language_stop_words(&rtxn, "fr").unwrap().is_some());
+    }
+
+    #[test]
+    fn reindex_keeps_the_language_stop_words() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_stop_words_for_language(S("en"), vec![S("the")]);
+        builder.execute(|_, _| ()).unwrap();
+
+        let content = &br#"[{ "id": 0, "text": "the cat" }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Json);
+        builder.language("en");
+        builder.execute(content, |_, _| ()).unwrap();
+
+        let content = &br#"[{ "id": 1, "text": "the dog" }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 2);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let the = index.word_docids.get(&rtxn, "the").unwrap().unwrap();
+        assert_eq!(the.iter().collect::<Vec<_>>(), vec![1]);
+        let languages = index.languages_documents_ids(&rtxn).unwrap();
+        assert_eq!(languages["en"].iter().collect::<Vec<_>>(), vec![0]);
+        drop(rtxn);
+
+        // Changing the searchable fields reindexes all the documents.
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 3);
+        builder.set_searchable_fields(vec![S("text"), S("id")]);
+        builder.execute(|_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // The english document is still indexed without its stop words.
+        let rtxn = index.read_txn().unwrap();
+        let the = index.word_docids.get(&rtxn, "the").unwrap().unwrap();
+        assert_eq!(the.iter().collect::<Vec<_>>(), vec![1]);
+        assert!(index.word_docids.get(&rtxn, "cat").unwrap().unwrap().contains(0));
+        let languages = index.languages_documents_ids(&rtxn).unwrap();
+        assert_eq!(languages["en"].iter().collect::<Vec<_>>(), vec![0]);
+        drop(rtxn);
+
+        // The deleted documents are removed from their language.
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index, 4).unwrap();
+        builder.delete_external_id("0");
+        builder.execute().unwrap();
+        assert!(index.languages_documents_ids(&wtxn).unwrap().is_empty());
+        wtxn.commit().unwrap();
+    }
+
     #[test]
     fn set_and_reset_synonyms() {
         let path = tempfile::tempdir().unwrap();
@@ -0,0 +1,77 @@
+use serde_json::{Map, Value};
+
+use super::index_documents::extract_external_id;
+use crate::error::{InternalError, UserError};
+use crate::update::{DocumentAdditionResult, IndexDocuments, IndexDocumentsMethod, UpdateFormat};
+use crate::{Index, Result};
+
+/// Inserts the document or replaces the one with the same external id.
+///
+/// The `external_id` is validated like the ids of the indexed documents and written under
+/// the primary key of the document, the index must therefore already have a primary key.
+/// This is meant for low-volume single document updates, indexing documents by batches
+/// with [`IndexDocuments`] is still much more efficient when there are many documents to update.
+pub fn upsert_document<'i>(
+    wtxn: &mut heed::RwTxn<'i, '_>,
+    index: &'i Index,
+    update_id: u64,
+    external_id: &str,
+    mut document: Map<String, Value>,
+) -> Result<DocumentAdditionResult> {
+    let primary_key = index.primary_key(wtxn)?.ok_or(UserError::MissingPrimaryKey)?.to_string();
+    let primary_key_policy = index.primary_key_policy(wtxn)?;
+
+    let value = Value::from(external_id);
+    if let Err(document_id) = extract_external_id(&primary_key_policy, &value) {
+        return Err(UserError::InvalidDocumentId { document_id }.into());
+    }
+    document.insert(primary_key, value);
+    let content = serde_json::to_vec(&document).map_err(InternalError::SerdeJson)?;
+
+    let mut builder = IndexDocuments::new(wtxn, index, update_id);
+    builder.update_format(UpdateFormat::JsonStream);
+    builder.index_documents_method(IndexDocumentsMethod::ReplaceDocuments);
+    builder.execute(&content[..], |_, _| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use heed::EnvOpenOptions;
+    use serde_json::json;
+
+    use super::*;
+    use crate::update::Settings;
+
+    #[test]
+    fn upsert_the_same_document_twice() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_primary_key("id".to_string());
+        builder.execute(|_, _| ()).unwrap();
+
+        let document = json!({ "name": "kevin", "age": 20 });
+        upsert_document(&mut wtxn, &index, 1, "1", document.as_object().unwrap().clone()).unwrap();
+        let document = json!({ "name": "kevina" });
+        upsert_document(&mut wtxn, &index, 2, "1", document.as_object().unwrap().clone()).unwrap();
+
+        // The external id is validated like the ones of the indexed documents.
+        let document = json!({ "name": "bob" });
+        match upsert_document(&mut wtxn, &index, 3, "a b", document.as_object().unwrap().clone()) {
+            Err(crate::Error::UserError(UserError::InvalidDocumentId { document_id })) => {
+                assert_eq!(document_id, json!("a b"));
+            }
+            otherwise => panic!("expected an invalid document id error, got {:?}", otherwise),
+        }
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 1);
+        let documents = index.documents_json(&rtxn, index.documents_ids(&rtxn).unwrap(), None);
+        assert_eq!(documents.unwrap(), vec![json!({ "id": "1", "name": "kevina" })]);
+    }
+}
@@ -1,6 +1,7 @@
+use std::borrow::Cow;
 use std::convert::TryFrom;
 use std::fs::File;
-use std::num::NonZeroU32;
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::{cmp, str};
 
 use fst::Streamer;
@@ -8,6 +9,9 @@ use grenad::{CompressionType, Reader, Writer};
 use heed::types::{ByteSlice, DecodeIgnore, Str};
 use heed::{BytesEncode, Error};
 use log::debug;
+#[cfg(not(feature = "sequential-words-levels"))]
+use rayon::prelude::*;
+use rayon::ThreadPool;
 use roaring::RoaringBitmap;
 
 use crate::error::{InternalError, SerializationError};
@@ -19,22 +23,40 @@ use crate::update::index_documents::{
 };
 use crate::{Index, Result, TreeLevel};
 
-pub struct WordsLevelPositions<'t, 'u, 'i> {
+/// The default number of level 0 entries that are read in memory before their words levels
+/// are computed in parallel, the words of a batch are split between the available threads.
+const LEVEL_0_ENTRIES_BATCH_SIZE: usize = 100_000;
+
+/// The level 0 entries of a word, the left and right positions and the documents ids.
+type WordLevel0Entries = (String, Vec<(u32, u32, RoaringBitmap)>);
+
+/// The shape of the levels generated from the level 0 entries.
+#[derive(Debug, Clone, Copy)]
+struct LevelsParameters {
+    group_size: NonZeroU32,
+    min_level_size: NonZeroU32,
+    max_level: Option<u8>,
+}
+
+pub struct WordsLevelPositions<'t, 'u, 'i, 'a> {
     wtxn: &'t mut heed::RwTxn<'i, 'u>,
     index: &'i Index,
     pub(crate) chunk_compression_type: CompressionType,
     pub(crate) chunk_compression_level: Option<u32>,
     pub(crate) max_nb_chunks: Option<usize>,
     pub(crate) max_memory: Option<usize>,
+    pub(crate) thread_pool: Option<&'a ThreadPool>,
     level_group_size: NonZeroU32,
     min_level_size: NonZeroU32,
+    max_level: Option<u8>,
+    level_0_entries_batch_size: NonZeroUsize,
 }
 
-impl<'t, 'u, 'i> WordsLevelPositions<'t, 'u, 'i> {
+impl<'t, 'u, 'i, 'a> WordsLevelPositions<'t, 'u, 'i, 'a> {
     pub fn new(
         wtxn: &'t mut heed::RwTxn<'i, 'u>,
         index: &'i Index,
-    ) -> WordsLevelPositions<'t, 'u, 'i> {
+    ) -> WordsLevelPositions<'t, 'u, 'i, 'a> {
         WordsLevelPositions {
             wtxn,
             index,
@@ -42,8 +64,11 @@ impl<'t, 'u, 'i> WordsLevelPositions<'t, 'u, 'i> {
             chunk_compression_level: None,
             max_nb_chunks: None,
             max_memory: None,
+            thread_pool: None,
             level_group_size: NonZeroU32::new(4).unwrap(),
             min_level_size: NonZeroU32::new(5).unwrap(),
+            max_level: None,
+            level_0_entries_batch_size: NonZeroUsize::new(LEVEL_0_ENTRIES_BATCH_SIZE).unwrap(),
         }
     }
 
@@ -57,31 +82,66 @@ impl<'t, 'u, 'i> WordsLevelPositions<'t, 'u, 'i> {
         self
     }
 
+    /// The maximum number of levels generated, including the level 0, unbounded by default.
+    pub fn max_level(&mut self, value: u8) -> &mut Self {
+        self.max_level = Some(value);
+        self
+    }
+
+    /// The compression used by the temporary files written while computing the levels.
+    pub fn chunk_compression_type(&mut self, value: CompressionType) -> &mut Self {
+        self.chunk_compression_type = value;
+        self
+    }
+
+    pub fn chunk_compression_level(&mut self, value: u32) -> &mut Self {
+        self.chunk_compression_level = Some(value);
+        self
+    }
+
+    /// The number of level 0 entries read in memory before their levels are computed.
+    pub fn level_0_entries_batch_size(&mut self, value: NonZeroUsize) -> &mut Self {
+        self.level_0_entries_batch_size = value;
+        self
+    }
+
+    /// The levels are computed in this pool, in the current one if none is given.
+    pub fn thread_pool(&mut self, pool: &'a ThreadPool) -> &mut Self {
+        self.thread_pool = Some(pool);
+        self
+    }
+
+    fn levels_parameters(&self) -> LevelsParameters {
+        LevelsParameters {
+            group_size: self.level_group_size,
+            min_level_size: self.min_level_size,
+            max_level: self.max_level,
+        }
+    }
+
     #[logging_timer::time("WordsLevelPositions::{}")]
     pub fn execute(self) -> Result<()> {
         debug!("Computing and writing the word levels positions docids into LMDB on disk...");
 
-        let entries = compute_positions_levels(
-            self.wtxn,
+        let entries = self.compute_positions_levels(
             self.index.word_docids.remap_data_type::<DecodeIgnore>(),
             self.index.word_level_position_docids,
-            self.chunk_compression_type,
-            self.chunk_compression_level,
-            self.level_group_size,
-            self.min_level_size,
         )?;
 
         // The previously computed entries also defines the level 0 entries
         // so we can clear the database and append all of these entries.
         self.index.word_level_position_docids.clear(self.wtxn)?;
 
-        write_into_lmdb_database(
-            self.wtxn,
-            *self.index.word_level_position_docids.as_polymorph(),
-            entries,
-            |_, _| Err(InternalError::IndexingMergingKeys { process: "word level position" })?,
-            WriteMethod::Append,
-        )?;
+        // The readers contain sorted and disjoint ranges of words, in order.
+        for entries in entries {
+            write_into_lmdb_database(
+                self.wtxn,
+                *self.index.word_level_position_docids.as_polymorph(),
+                entries,
+                merge_word_level_positions,
+                WriteMethod::Append,
+            )?;
+        }
 
         // We compute the word prefix level positions database.
         self.index.word_prefix_level_position_docids.clear(self.wtxn)?;
@@ -132,32 +192,99 @@ impl<'t, 'u, 'i> WordsLevelPositions<'t, 'u, 'i> {
             WriteMethod::Append,
         )?;
 
-        let entries = compute_positions_levels(
-            self.wtxn,
+        let entries = self.compute_positions_levels(
             self.index.word_prefix_docids.remap_data_type::<DecodeIgnore>(),
             self.index.word_prefix_level_position_docids,
-            self.chunk_compression_type,
-            self.chunk_compression_level,
-            self.level_group_size,
-            self.min_level_size,
         )?;
 
         // The previously computed entries also defines the level 0 entries
         // so we can clear the database and append all of these entries.
         self.index.word_prefix_level_position_docids.clear(self.wtxn)?;
 
-        write_into_lmdb_database(
-            self.wtxn,
-            *self.index.word_prefix_level_position_docids.as_polymorph(),
-            entries,
-            |_, _| {
-                Err(InternalError::IndexingMergingKeys { process: "word prefix level position" })?
-            },
-            WriteMethod::Append,
-        )?;
+        for entries in entries {
+            write_into_lmdb_database(
+                self.wtxn,
+                *self.index.word_prefix_level_position_docids.as_polymorph(),
+                entries,
+                merge_word_prefix_level_positions,
+                WriteMethod::Append,
+            )?;
+        }
 
         Ok(())
     }
+
+    /// Generates all the words positions levels based on the levels zero (including the level
+    /// zero).
+    ///
+    /// The level 0 entries are read by batches, as LMDB doesn't allow to read the write
+    /// transaction from other threads, and the levels of the words of a batch are computed in
+    /// parallel. The returned readers contain sorted and disjoint ranges of words and are
+    /// returned in order.
+    fn compute_positions_levels(
+        &self,
+        words_db: heed::Database<Str, DecodeIgnore>,
+        words_positions_db: heed::Database<StrLevelPositionCodec, CboRoaringBitmapCodec>,
+    ) -> Result<Vec<Reader<File>>> {
+        let compute_batch = |batch: &[WordLevel0Entries]| {
+            compute_words_levels(
+                batch,
+                self.chunk_compression_type,
+                self.chunk_compression_level,
+                self.levels_parameters(),
+                self.thread_pool,
+            )
+        };
+
+        let rtxn: &heed::RoTxn = &*self.wtxn;
+        let mut readers = Vec::new();
+        let mut batch = Vec::new();
+        let mut batch_entries = 0;
+        for result in words_db.iter(rtxn)? {
+            let (word, ()) = result?;
+
+            let level_0_range = {
+                let left = (word, TreeLevel::min_value(), u32::min_value(), u32::min_value());
+                let right = (word, TreeLevel::min_value(), u32::max_value(), u32::max_value());
+                left..=right
+            };
+
+            let mut entries = Vec::new();
+            for result in words_positions_db.range(rtxn, &level_0_range)? {
+                let ((_word, _level, left, right), docids) = result?;
+                entries.push((left, right, docids));
+            }
+
+            batch_entries += entries.len();
+            batch.push((word.to_string(), entries));
+            if batch_entries >= self.level_0_entries_batch_size.get() {
+                readers.extend(compute_batch(&batch)?);
+                batch.clear();
+                batch_entries = 0;
+            }
+        }
+
+        if !batch.is_empty() {
+            readers.extend(compute_batch(&batch)?);
+        }
+
+        Ok(readers)
+    }
+}
+
+/// The word level positions entries are appended into a cleared database,
+/// no key can be merged.
+fn merge_word_level_positions<'a>(_key: &[u8], _values: &[Cow<'a, [u8]>]) -> Result<Cow<'a, [u8]>> {
+    Err(InternalError::IndexingMergingKeys { process: "word level position" }.into())
+}
+
+/// The word prefix level positions entries are appended into a cleared database,
+/// no key can be merged.
+fn merge_word_prefix_level_positions<'a>(
+    _key: &[u8],
+    _values: &[Cow<'a, [u8]>],
+) -> Result<Cow<'a, [u8]>> {
+    Err(InternalError::IndexingMergingKeys { process: "word prefix level position" }.into())
 }
 
 /// Returns the next number after or equal to `x` that is divisible by `d`.
@@ -174,82 +301,103 @@ fn previous_divisible(x: u32, d: u32) -> u32 {
     }
 }
 
-/// Generates all the words positions levels based on the levels zero (including the level zero).
-fn compute_positions_levels(
-    rtxn: &heed::RoTxn,
-    words_db: heed::Database<Str, DecodeIgnore>,
-    words_positions_db: heed::Database<StrLevelPositionCodec, CboRoaringBitmapCodec>,
+/// Computes the levels of the words by splitting them between the threads of the pool, unless
+/// the `sequential-words-levels` feature is enabled, each thread writes into its own file.
+fn compute_words_levels(
+    words: &[WordLevel0Entries],
     compression_type: CompressionType,
     compression_level: Option<u32>,
-    level_group_size: NonZeroU32,
-    min_level_size: NonZeroU32,
-) -> Result<Reader<File>> {
-    // It is forbidden to keep a cursor and write in a database at the same time with LMDB
-    // therefore we write the facet levels entries into a grenad file before transfering them.
-    let mut writer = tempfile::tempfile()
-        .and_then(|file| create_writer(compression_type, compression_level, file))?;
-
-    for result in words_db.iter(rtxn)? {
-        let (word, ()) = result?;
-
-        let level_0_range = {
-            let left = (word, TreeLevel::min_value(), u32::min_value(), u32::min_value());
-            let right = (word, TreeLevel::min_value(), u32::max_value(), u32::max_value());
-            left..=right
+    parameters: LevelsParameters,
+    thread_pool: Option<&ThreadPool>,
+) -> Result<Vec<Reader<File>>> {
+    let compute = |words: &[WordLevel0Entries]| -> Result<Reader<File>> {
+        // It is forbidden to keep a cursor and write in a database at the same time with LMDB
+        // therefore we write the levels entries into a grenad file before transfering them.
+        let mut writer = tempfile::tempfile()
+            .and_then(|file| create_writer(compression_type, compression_level, file))?;
+        for (word, entries) in words {
+            write_word_levels(&mut writer, word, entries, parameters)?;
+        }
+        writer_into_reader(writer)
+    };
+
+    #[cfg(not(feature = "sequential-words-levels"))]
+    {
+        let compute_in_parallel = || {
+            let threads = rayon::current_num_threads();
+            let words_per_thread = cmp::max(1, (words.len() + threads - 1) / threads);
+            words.par_chunks(words_per_thread).map(compute).collect()
         };
 
-        let first_level_size = words_positions_db
-            .remap_data_type::<DecodeIgnore>()
-            .range(rtxn, &level_0_range)?
-            .fold(Ok(0u32), |count, result| result.and(count).map(|c| c + 1))?;
-
-        // Groups sizes are always a power of the original level_group_size and therefore a group
-        // always maps groups of the previous level and never splits previous levels groups in half.
-        let group_size_iter = (1u8..)
-            .map(|l| (TreeLevel::try_from(l).unwrap(), level_group_size.get().pow(l as u32)))
-            .take_while(|(_, s)| first_level_size / *s >= min_level_size.get());
-
-        // As specified in the documentation, we also write the level 0 entries.
-        for result in words_positions_db.range(rtxn, &level_0_range)? {
-            let ((word, level, left, right), docids) = result?;
-            write_level_entry(&mut writer, word, level, left, right, &docids)?;
+        match thread_pool {
+            Some(pool) => pool.install(compute_in_parallel),
+            None => compute_in_parallel(),
         }
+    }
 
-        for (level, group_size) in group_size_iter {
-            let mut left = 0;
-            let mut right = 0;
-            let mut group_docids = RoaringBitmap::new();
-
-            for (i, result) in words_positions_db.range(rtxn, &level_0_range)?.enumerate() {
-                let ((_word, _level, value, _right), docids) = result?;
+    #[cfg(feature = "sequential-words-levels")]
+    {
+        let _ = thread_pool;
+        compute(words).map(|reader| vec![reader])
+    }
+}
 
-                if i == 0 {
-                    left = previous_divisible(value, group_size);
-                    right = left + (group_size - 1);
-                }
+/// Writes the level 0 entries of a word followed by all the levels computed from them.
+fn write_word_levels(
+    writer: &mut Writer<File>,
+    word: &str,
+    entries: &[(u32, u32, RoaringBitmap)],
+    parameters: LevelsParameters,
+) -> Result<()> {
+    let LevelsParameters { group_size, min_level_size, max_level } = parameters;
+    let first_level_size = entries.len() as u32;
+
+    // Groups sizes are always a power of the original level_group_size and therefore a group
+    // always maps groups of the previous level and never splits previous levels groups in half.
+    let group_size_iter = (1u8..)
+        .take_while(|l| max_level.map_or(true, |max| *l < max))
+        .map(|l| (TreeLevel::try_from(l).unwrap(), group_size.get().pow(l as u32)))
+        .take_while(|(_, s)| first_level_size / *s >= min_level_size.get());
+
+    // As specified in the documentation, we also write the level 0 entries.
+    for (left, right, docids) in entries {
+        write_level_entry(writer, word, TreeLevel::min_value(), *left, *right, docids)?;
+    }
 
-                if value > right {
-                    // we found the first bound of the next group, we must store the left
-                    // and right bounds associated with the docids.
-                    write_level_entry(&mut writer, word, level, left, right, &group_docids)?;
+    for (level, group_size) in group_size_iter {
+        let mut left = 0;
+        let mut right = 0;
+        let mut group_docids = RoaringBitmap::new();
 
-                    // We save the left bound for the new group and also reset the docids.
-                    group_docids = RoaringBitmap::new();
-                    left = previous_divisible(value, group_size);
-                    right = left + (group_size - 1);
-                }
+        for (i, (value, _right, docids)) in entries.iter().enumerate() {
+            let value = *value;
 
-                // The right bound is always the bound we run through.
-                group_docids |= docids;
+            if i == 0 {
+                left = previous_divisible(value, group_size);
+                right = left + (group_size - 1);
             }
 
-            if !group_docids.is_empty() {
-                write_level_entry(&mut writer, word, level, left, right, &group_docids)?;
+            if value > right {
+                // we found the first bound of the next group, we must store the left
+                // and right bounds associated with the docids.
+                write_level_entry(writer, word, level, left, right, &group_docids)?;
+
+                // We save the left bound for the new group and also reset the docids.
+                group_docids = RoaringBitmap::new();
+                left = previous_divisible(value, group_size);
+                right = left + (group_size - 1);
             }
+
+            // The right bound is always the bound we run through.
+            group_docids |= docids;
+        }
+
+        if !group_docids.is_empty() {
+            write_level_entry(writer, word, level, left, right, &group_docids)?;
         }
     }
 
-    writer_into_reader(writer)
+    Ok(())
 }
 
 fn write_level_entry(
@@ -266,3 +414,246 @@ fn write_level_entry(
     writer.insert(&key, &data)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use heed::EnvOpenOptions;
+    use maplit::hashset;
+
+    use super::*;
+    use crate::update::{IndexDocuments, Settings, UpdateFormat};
+    use crate::Error;
+
+    #[test]
+    fn levels_are_written_into_the_word_level_position_database() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let text = vec!["hello"; 40].join(" ");
+        let content = serde_json::to_vec(&serde_json::json!([{ "id": 1, "text": text }])).unwrap();
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(&content[..], |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        // A second update recomputes the levels from the level 0 entries.
+        let content = &br#"[{ "id": 2, "text": "hello" }]"#[..];
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut levels = Vec::new();
+        for result in index.word_level_position_docids.iter(&rtxn).unwrap() {
+            let ((word, level, _left, _right), docids) = result.unwrap();
+            assert_eq!(word, "hello");
+            if level != TreeLevel::min_value() {
+                assert!(docids.contains(0));
+            }
+            levels.push(level);
+        }
+        assert!(levels.contains(&TreeLevel::min_value()));
+        assert!(levels.iter().any(|l| *l != TreeLevel::min_value()));
+
+        // Nothing has been written into the facet databases.
+        assert!(index.facet_id_f64_docids.iter(&rtxn).unwrap().next().is_none());
+        assert!(index.facet_id_string_docids.iter(&rtxn).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn custom_chunk_compression() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let text = vec!["hello"; 40].join(" ");
+        let content = serde_json::to_vec(&serde_json::json!([{ "id": 1, "text": text }])).unwrap();
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(&content[..], |_, _| ()).unwrap();
+
+        let before = index.word_level_position_docids.iter(&wtxn).unwrap().count();
+
+        let mut builder = WordsLevelPositions::new(&mut wtxn, &index);
+        builder.chunk_compression_type(CompressionType::None).chunk_compression_level(1);
+        builder.execute().unwrap();
+
+        let after = index.word_level_position_docids.iter(&wtxn).unwrap().count();
+        assert_eq!(before, after);
+        wtxn.commit().unwrap();
+    }
+
+    #[test]
+    fn merge_collision_is_an_internal_error() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let content = &br#"[{ "id": 1, "text": "hello world" }]"#[..];
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(content, |_, _| ()).unwrap();
+
+        // We write an entry that already exists in the database.
+        let db = index.word_level_position_docids.remap_types::<ByteSlice, ByteSlice>();
+        let (key, value) = db.first(&wtxn).unwrap().unwrap();
+        let (key, value) = (key.to_vec(), value.to_vec());
+        let mut writer =
+            create_writer(CompressionType::None, None, tempfile::tempfile().unwrap()).unwrap();
+        writer.insert(&key, &value).unwrap();
+        let reader = writer_into_reader(writer).unwrap();
+
+        let result = write_into_lmdb_database(
+            &mut wtxn,
+            *index.word_level_position_docids.as_polymorph(),
+            reader,
+            merge_word_level_positions,
+            WriteMethod::GetMergePut,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::InternalError(InternalError::IndexingMergingKeys {
+                process: "word level position"
+            }))
+        ));
+    }
+
+    #[test]
+    fn words_levels_computed_by_batches() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        // Enough words and positions to be split into several batches.
+        let words: Vec<_> = (0..20).map(|i| format!("word{}", i)).collect();
+        let text = vec![words.join(" "); 40].join(" ");
+        let content = serde_json::to_vec(&serde_json::json!([{ "id": 1, "text": text }])).unwrap();
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(&content[..], |_, _| ()).unwrap();
+
+        let db = index.word_level_position_docids.remap_types::<ByteSlice, ByteSlice>();
+        let entries = |wtxn: &heed::RwTxn| -> Vec<(Vec<u8>, Vec<u8>)> {
+            let iter = db.iter(wtxn).unwrap().map(|r| r.unwrap());
+            iter.map(|(k, v)| (k.to_vec(), v.to_vec())).collect()
+        };
+        let before = entries(&wtxn);
+
+        // Every word has its level 0 entries and a first level.
+        for word in &words {
+            let mut levels = Vec::new();
+            let iter = index.word_level_position_docids.iter(&wtxn).unwrap();
+            for result in iter {
+                let ((w, level, _left, _right), _docids) = result.unwrap();
+                if w == word {
+                    levels.push(level);
+                }
+            }
+            assert_eq!(levels.iter().filter(|l| **l == TreeLevel::min_value()).count(), 40);
+            assert!(levels.iter().any(|l| *l != TreeLevel::min_value()));
+        }
+
+        // Recomputing the levels by small batches, in a dedicated pool, gives the same entries.
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        let mut builder = WordsLevelPositions::new(&mut wtxn, &index);
+        builder.level_0_entries_batch_size(NonZeroUsize::new(64).unwrap()).thread_pool(&pool);
+        builder.execute().unwrap();
+        assert_eq!(before, entries(&wtxn));
+        wtxn.commit().unwrap();
+    }
+
+    #[test]
+    fn max_level() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let text = vec!["hello"; 200].join(" ");
+        let content = serde_json::to_vec(&serde_json::json!([{ "id": 1, "text": text }])).unwrap();
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 0);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(&content[..], |_, _| ()).unwrap();
+
+        let max_level = |wtxn: &heed::RwTxn| {
+            let iter = index.word_level_position_docids.iter(wtxn).unwrap();
+            let levels = iter.map(|result| {
+                let ((_word, level, _left, _right), _docids) = result.unwrap();
+                Into::<u8>::into(level)
+            });
+            levels.max().unwrap()
+        };
+        // Unbounded by default.
+        assert_eq!(max_level(&wtxn), 2);
+
+        let mut builder = WordsLevelPositions::new(&mut wtxn, &index);
+        builder.max_level(2);
+        builder.execute().unwrap();
+        assert_eq!(max_level(&wtxn), 1);
+
+        let mut builder = WordsLevelPositions::new(&mut wtxn, &index);
+        builder.max_level(1);
+        builder.execute().unwrap();
+        assert_eq!(max_level(&wtxn), 0);
+        wtxn.commit().unwrap();
+    }
+
+    #[test]
+    fn execute_leaves_the_facet_databases_untouched() {
+        let path = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(10 * 1024 * 1024); // 10 MB
+        let index = Index::new(options, &path).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = Settings::new(&mut wtxn, &index, 0);
+        builder.set_filterable_fields(hashset! { S("price"), S("tag") });
+        builder.execute(|_, _| ()).unwrap();
+
+        let text = vec!["hello"; 40].join(" ");
+        let content = serde_json::to_vec(&serde_json::json!([
+            { "id": 1, "text": text, "price": 12, "tag": "red" },
+            { "id": 2, "text": "hello world", "price": 3.5, "tag": "blue" }
+        ]))
+        .unwrap();
+        let mut builder = IndexDocuments::new(&mut wtxn, &index, 1);
+        builder.update_format(UpdateFormat::Json);
+        builder.execute(&content[..], |_, _| ()).unwrap();
+
+        let facets = |wtxn: &heed::RwTxn| -> Vec<(Vec<u8>, Vec<u8>)> {
+            let numbers = index.facet_id_f64_docids.remap_types::<ByteSlice, ByteSlice>();
+            let strings = index.facet_id_string_docids.remap_types::<ByteSlice, ByteSlice>();
+            let iter = numbers.iter(wtxn).unwrap().chain(strings.iter(wtxn).unwrap());
+            iter.map(|r| r.unwrap()).map(|(k, v)| (k.to_vec(), v.to_vec())).collect()
+        };
+        let before = facets(&wtxn);
+        assert!(!before.is_empty());
+
+        WordsLevelPositions::new(&mut wtxn, &index).execute().unwrap();
+        assert_eq!(before, facets(&wtxn));
+
+        // The word levels can be queried with the word level position codec.
+        let level = TreeLevel::try_from(1).unwrap();
+        let range = ("hello", level, u32::min_value(), u32::min_value())
+            ..=("hello", level, u32::max_value(), u32::max_value());
+        let mut iter = index.word_level_position_docids.range(&wtxn, &range).unwrap();
+        let ((_word, _level, left, right), docids) = iter.next().unwrap().unwrap();
+        assert!(left < right);
+        assert!(docids.contains(0));
+        drop(iter);
+        wtxn.commit().unwrap();
+    }
+}
@@ -125,9 +125,11 @@ pub fn expected_order(
                     new_groups
                         .extend(group.linear_group_by_key(|d| d.asc_desc_rank).map(Vec::from));
                 }
-                Criterion::Asc(_) | Criterion::Desc(_) | Criterion::Sort => {
-                    new_groups.push(group.clone())
-                }
+                Criterion::Asc(_)
+                | Criterion::Desc(_)
+                | Criterion::Sort
+                | Criterion::WordCount
+                | Criterion::RankingScore => new_groups.push(group.clone()),
             }
         }
         groups = std::mem::take(&mut new_groups);
@@ -1,5 +1,5 @@
 use big_s::S;
-use milli::Criterion::{Attribute, Exactness, Proximity, Typo, Words};
+use milli::Criterion::{Attribute, Exactness, Proximity, Sort, Typo, Words};
 use milli::{AscDesc, Error, Search, UserError};
 
 use crate::search::{self, EXTERNAL_DOCUMENTS_IDS};
@@ -21,3 +21,23 @@ fn sort_ranking_rule_missing() {
     let result = search.execute();
     assert!(matches!(result, Err(Error::UserError(UserError::SortRankingRuleMissing))));
 }
+
+#[test]
+fn sort_on_non_sortable_field() {
+    let criteria = vec![Words, Sort, Typo, Proximity, Attribute, Exactness];
+    // sortables: `tag` and `asc_desc_rank`
+    let index = search::setup_search_index_with_criteria(&criteria);
+    let rtxn = index.read_txn().unwrap();
+
+    let mut search = Search::new(&rtxn, &index);
+    search.query(search::TEST_QUERY);
+    search.sort_criteria(vec![AscDesc::Asc(S("tag")), AscDesc::Desc(S("title"))]);
+
+    let result = search.execute();
+    match result {
+        Err(Error::UserError(UserError::InvalidSortableAttribute { field, .. })) => {
+            assert_eq!(field, "title")
+        }
+        otherwise => panic!("unexpected result {:?}", otherwise.map(|r| r.documents_ids)),
+    }
+}
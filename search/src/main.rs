@@ -1,6 +1,7 @@
 use std::io::{self, BufRead, Write};
 use std::iter::once;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Instant;
 
 use byte_unit::Byte;
@@ -36,6 +37,35 @@ pub struct Opt {
     /// Compute and print the facet distribution of all the faceted fields.
     #[structopt(long)]
     print_facet_distribution: bool,
+
+    /// Compute and print the facet distribution of these filterable fields only.
+    #[structopt(long)]
+    facets: Vec<String>,
+
+    /// How the documents are printed: `jsonl` prints one document by line,
+    /// `json` and `pretty` print the documents of each query as a JSON array.
+    #[structopt(long, default_value = "jsonl", possible_values = &["pretty", "json", "jsonl"])]
+    output: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    Pretty,
+    Json,
+    JsonLines,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<OutputFormat> {
+        match s {
+            "pretty" => Ok(OutputFormat::Pretty),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::JsonLines),
+            otherwise => anyhow::bail!("invalid output format {:?}", otherwise),
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
@@ -78,15 +108,37 @@ fn main() -> anyhow::Result<()> {
         let result = index.search(&rtxn).query(query).execute()?;
         let documents = index.documents(&rtxn, result.documents_ids.iter().cloned())?;
 
+        let mut hits = Vec::new();
         for (_id, record) in documents {
             let val = obkv_to_json(&displayed_fields, &fields_ids_map, record)?;
-            serde_json::to_writer(&mut stdout, &val)?;
-            let _ = writeln!(&mut stdout);
+            match opt.output {
+                OutputFormat::JsonLines => {
+                    serde_json::to_writer(&mut stdout, &val)?;
+                    let _ = writeln!(&mut stdout);
+                    stdout.flush()?;
+                }
+                OutputFormat::Pretty | OutputFormat::Json => hits.push(val),
+            }
+        }
+
+        match opt.output {
+            OutputFormat::Pretty => {
+                serde_json::to_writer_pretty(&mut stdout, &hits)?;
+                let _ = writeln!(&mut stdout);
+            }
+            OutputFormat::Json => {
+                serde_json::to_writer(&mut stdout, &hits)?;
+                let _ = writeln!(&mut stdout);
+            }
+            OutputFormat::JsonLines => (),
         }
 
-        if opt.print_facet_distribution {
-            let facets =
-                index.facets_distribution(&rtxn).candidates(result.candidates).execute()?;
+        if opt.print_facet_distribution || !opt.facets.is_empty() {
+            let mut builder = index.facets_distribution(&rtxn);
+            if !opt.facets.is_empty() {
+                builder.facets(&opt.facets);
+            }
+            let facets = builder.candidates(result.candidates).execute()?;
             serde_json::to_writer(&mut stdout, &facets)?;
             let _ = writeln!(&mut stdout);
         }